@@ -0,0 +1,444 @@
+/// Loss analysis: re-search every NN move from `compete` output with a deep baseline
+/// search and report where the net went wrong.
+///
+/// Usage:
+///   analyze <results.json | games.pgn>... [--depth N] [--threshold CP] [--level N]
+///           [--losses-only] [--side white|black|both]
+///
+/// JSON input is `compete --json-output`; the NN side comes from each game's `color`.
+/// PGN input uses the player named "NnEvalBot" (as written by `compete --pgn-output`),
+/// falling back to `--side` (default: both sides).
+///
+/// Moves losing at least `--threshold` centipawns against the best move are flagged as
+/// inaccuracy (>= 50), mistake (>= 100) or blunder (>= 300), tagged with a motif, and
+/// summarised by game phase and motif.
+use cli::args::parse_or_exit;
+use engine::analysis::{analyze_move, Motif, Phase, Severity, INACCURACY_CP};
+use engine::game::STARTPOS;
+use engine::json::{parse_json, Json};
+use engine::pgn::parse_pgn;
+use engine::{format_san, parse_legal_uci_move, parse_san, Board, Color, Move, SearchContext};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const DEFAULT_DEPTH: u32 = 5;
+
+/// A game to analyse: its start position, moves, and which side(s) the NN played.
+struct GameInput {
+    label: String,
+    start: Board,
+    moves: Vec<Move>,
+    /// `None` when the NN side is unknown and both sides are analysed.
+    nn_side: Option<Color>,
+    level: Option<u8>,
+    lost: bool,
+}
+
+/// A flagged move, for the per-game listing.
+struct Flag {
+    ply_label: String,
+    played: String,
+    best: String,
+    loss: i32,
+    severity: Severity,
+    motif: Motif,
+    phase: Phase,
+}
+
+#[derive(Default)]
+struct PhaseStats {
+    moves: usize,
+    total_loss: i64,
+    by_severity: [usize; 3],
+}
+
+fn severity_index(s: Severity) -> usize {
+    match s {
+        Severity::Inaccuracy => 0,
+        Severity::Mistake => 1,
+        Severity::Blunder => 2,
+    }
+}
+
+fn annotation(s: Severity) -> &'static str {
+    match s {
+        Severity::Inaccuracy => "?!",
+        Severity::Mistake => "?",
+        Severity::Blunder => "??",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Input loading
+// ---------------------------------------------------------------------------
+
+fn games_from_json(doc: &Json, source: &str) -> Result<Vec<GameInput>, String> {
+    let levels = doc
+        .get("levels")
+        .and_then(Json::as_array)
+        .ok_or_else(|| format!("{source}: missing \"levels\" array"))?;
+    let mut games = Vec::new();
+    for level in levels {
+        let level_num = level.get("level").and_then(Json::as_f64).map(|n| n as u8);
+        for game in level.get("games").and_then(Json::as_array).unwrap_or(&[]) {
+            let index = game.get("opening_index").and_then(Json::as_f64).unwrap_or(0.0) as usize;
+            let nn_side = match game.get("color").and_then(Json::as_str) {
+                Some("white") => Color::White,
+                Some("black") => Color::Black,
+                _ => {
+                    return Err(format!(
+                        "{source}: level {} game {} has no NN \"color\"",
+                        level_num.unwrap_or(0),
+                        games.len() + 1
+                    ))
+                }
+            };
+            let fen = game
+                .get("starting_fen")
+                .and_then(Json::as_str)
+                .unwrap_or(STARTPOS);
+            let start: Board = fen
+                .parse()
+                .map_err(|e| format!("{source}: invalid starting_fen {fen}: {e:?}"))?;
+
+            let mut board = start.clone();
+            let mut moves = Vec::new();
+            for mv in game.get("moves").and_then(Json::as_array).unwrap_or(&[]) {
+                let uci = mv.get("uci").and_then(Json::as_str).unwrap_or("");
+                let Some(m) = parse_legal_uci_move(&board, uci) else {
+                    return Err(format!("{source}: illegal move {uci} in {fen}"));
+                };
+                board.play_unchecked(m);
+                moves.push(m);
+            }
+
+            let opening = game
+                .get("opening_name")
+                .and_then(Json::as_str)
+                .map(|n| format!(" {n}"))
+                .unwrap_or_default();
+            games.push(GameInput {
+                label: format!(
+                    "L{} pos {}{} (NN {})",
+                    level_num.unwrap_or(0),
+                    index + 1,
+                    opening,
+                    if nn_side == Color::White {
+                        "white"
+                    } else {
+                        "black"
+                    }
+                ),
+                start,
+                moves,
+                nn_side: Some(nn_side),
+                level: level_num,
+                lost: game.get("outcome").and_then(Json::as_str) == Some("loss"),
+            });
+        }
+    }
+    Ok(games)
+}
+
+fn games_from_pgn(text: &str, source: &str, side: Option<Color>) -> Result<Vec<GameInput>, String> {
+    let mut games = Vec::new();
+    for (i, pgn) in parse_pgn(text).into_iter().enumerate() {
+        let fen = pgn.tag("FEN").unwrap_or(STARTPOS);
+        let start: Board = fen
+            .parse()
+            .map_err(|e| format!("{source} game {}: invalid FEN {fen}: {e:?}", i + 1))?;
+        let mut board = start.clone();
+        let mut moves = Vec::new();
+        for san in &pgn.moves {
+            let Some(m) = parse_san(&board, san) else {
+                return Err(format!("{source} game {}: illegal move {san}", i + 1));
+            };
+            board.play_unchecked(m);
+            moves.push(m);
+        }
+
+        let is_nn = |tag: &str| pgn.tag(tag).is_some_and(|name| name.contains("NnEvalBot"));
+        let nn_side = if is_nn("White") {
+            Some(Color::White)
+        } else if is_nn("Black") {
+            Some(Color::Black)
+        } else {
+            side
+        };
+        let lost = matches!(
+            (nn_side, pgn.tag("Result")),
+            (Some(Color::White), Some("0-1")) | (Some(Color::Black), Some("1-0"))
+        );
+        let level = pgn
+            .tag("Event")
+            .and_then(|e| e.rsplit(' ').next())
+            .and_then(|n| n.parse().ok());
+        let title = match (pgn.tag("White"), pgn.tag("Black")) {
+            (Some(w), Some(b)) => format!("{w} vs {b}"),
+            _ => format!("game {}", i + 1),
+        };
+        let round = pgn.tag("Round").map(|r| format!(" round {r}")).unwrap_or_default();
+        games.push(GameInput {
+            label: format!("{title}{round}"),
+            start,
+            moves,
+            nn_side,
+            level,
+            lost,
+        });
+    }
+    Ok(games)
+}
+
+fn load_games(path: &Path, side: Option<Color>) -> Result<Vec<GameInput>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let source = path.display().to_string();
+    if text.trim_start().starts_with('{') {
+        let doc = parse_json(&text).map_err(|e| format!("{source}: {e}"))?;
+        games_from_json(&doc, &source)
+    } else {
+        games_from_pgn(&text, &source, side)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Main
+// ---------------------------------------------------------------------------
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 || args[1] == "--help" || args[1] == "-h" {
+        eprintln!("Usage: analyze <results.json | games.pgn>... [--depth N] [--threshold CP] [--level N] [--losses-only] [--side white|black|both]");
+        eprintln!();
+        eprintln!("  --depth N         baseline search depth used to judge each move (default: {DEFAULT_DEPTH})");
+        eprintln!("  --threshold CP    only flag moves losing at least CP centipawns (default: {INACCURACY_CP})");
+        eprintln!("  --level N         only analyse games from level N");
+        eprintln!("  --losses-only     only analyse games the NN lost");
+        eprintln!("  --side S          NN side for PGN games without an NnEvalBot player (default: both)");
+        std::process::exit(1);
+    }
+
+    let mut inputs: Vec<String> = Vec::new();
+    let mut depth = DEFAULT_DEPTH;
+    let mut threshold = INACCURACY_CP;
+    let mut level_filter: Option<u8> = None;
+    let mut losses_only = false;
+    let mut side: Option<Color> = None;
+    {
+        let mut i = 1;
+        while i < args.len() {
+            let value = args.get(i + 1);
+            match args[i].as_str() {
+                "--depth" => {
+                    if let Some(val) = value {
+                        depth = parse_or_exit(val, "--depth");
+                        i += 1;
+                    }
+                }
+                "--threshold" => {
+                    if let Some(val) = value {
+                        threshold = parse_or_exit(val, "--threshold");
+                        if threshold < INACCURACY_CP {
+                            eprintln!(
+                                "Error: --threshold must be at least {INACCURACY_CP} \
+                                 (the smallest loss classed as an inaccuracy)"
+                            );
+                            std::process::exit(1);
+                        }
+                        i += 1;
+                    }
+                }
+                "--level" => {
+                    if let Some(val) = value {
+                        level_filter = Some(parse_or_exit(val, "--level"));
+                        i += 1;
+                    }
+                }
+                "--losses-only" => losses_only = true,
+                "--side" => {
+                    if let Some(val) = value {
+                        side = match val.as_str() {
+                            "white" => Some(Color::White),
+                            "black" => Some(Color::Black),
+                            "both" => None,
+                            _ => {
+                                eprintln!("Error: --side must be white, black or both");
+                                std::process::exit(1);
+                            }
+                        };
+                        i += 1;
+                    }
+                }
+                arg if !arg.starts_with("--") => inputs.push(arg.to_string()),
+                other => {
+                    eprintln!("Error: unknown flag {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    let mut games: Vec<GameInput> = Vec::new();
+    for input in &inputs {
+        match load_games(Path::new(input), side) {
+            Ok(g) => games.extend(g),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    games.retain(|g| {
+        (!losses_only || g.lost) && level_filter.is_none_or(|l| g.level == Some(l))
+    });
+    if games.is_empty() {
+        eprintln!("No games to analyse.");
+        std::process::exit(1);
+    }
+
+    println!(
+        "Analysing {} games at depth {} (threshold {}cp)",
+        games.len(),
+        depth,
+        threshold
+    );
+
+    let mut by_phase: BTreeMap<Phase, PhaseStats> = BTreeMap::new();
+    let mut by_motif: BTreeMap<Motif, [usize; 3]> = BTreeMap::new();
+
+    for (gi, game) in games.iter().enumerate() {
+        eprint!("\r  game {}/{}", gi + 1, games.len());
+        let mut ctx = SearchContext::new();
+        let mut board = game.start.clone();
+        let mut flags: Vec<Flag> = Vec::new();
+
+        for &mv in &game.moves {
+            let mover = board.side_to_move();
+            if game.nn_side.is_none_or(|c| c == mover) {
+                if let Some(a) = analyze_move(&mut ctx, &board, mv, depth) {
+                    let stats = by_phase.entry(a.phase).or_default();
+                    stats.moves += 1;
+                    stats.total_loss += a.loss as i64;
+                    if let Some(severity) = a.severity.filter(|_| a.loss >= threshold) {
+                        stats.by_severity[severity_index(severity)] += 1;
+                        by_motif.entry(a.motif).or_default()[severity_index(severity)] += 1;
+                        let number = board.fullmove_number();
+                        flags.push(Flag {
+                            ply_label: if mover == Color::White {
+                                format!("{number}.")
+                            } else {
+                                format!("{number}...")
+                            },
+                            played: format_san(&board, mv),
+                            best: format_san(&board, a.best),
+                            loss: a.loss,
+                            severity,
+                            motif: a.motif,
+                            phase: a.phase,
+                        });
+                    }
+                }
+            }
+            board.play_unchecked(mv);
+        }
+
+        if !flags.is_empty() {
+            eprint!("\r");
+            println!();
+            println!("{}{}", game.label, if game.lost { "  [loss]" } else { "" });
+            for f in &flags {
+                println!(
+                    "  {:>6} {:<9} best {:<8} {:>7}  {:<10}  {:<14}  {}",
+                    f.ply_label,
+                    format!("{}{}", f.played, annotation(f.severity)),
+                    f.best,
+                    format!("-{}cp", f.loss),
+                    f.severity.name(),
+                    f.motif.name(),
+                    f.phase.name(),
+                );
+            }
+        }
+    }
+    eprint!("\r");
+
+    // Summary by phase
+    println!();
+    println!("--- By game phase ---");
+    println!(
+        "  {:<11} {:>6} {:>6} {:>11} {:>8} {:>9}",
+        "Phase", "Moves", "ACPL", "Inaccuracy", "Mistake", "Blunder"
+    );
+    let mut total = PhaseStats::default();
+    for (phase, s) in &by_phase {
+        print_phase_row(phase.name(), s);
+        total.moves += s.moves;
+        total.total_loss += s.total_loss;
+        for k in 0..3 {
+            total.by_severity[k] += s.by_severity[k];
+        }
+    }
+    print_phase_row("total", &total);
+
+    // Summary by motif
+    println!();
+    println!("--- By motif ---");
+    println!(
+        "  {:<15} {:>11} {:>8} {:>9}",
+        "Motif", "Inaccuracy", "Mistake", "Blunder"
+    );
+    if by_motif.is_empty() {
+        println!("  (no flagged moves)");
+    }
+    for (motif, counts) in &by_motif {
+        println!(
+            "  {:<15} {:>11} {:>8} {:>9}",
+            motif.name(),
+            counts[0],
+            counts[1],
+            counts[2]
+        );
+    }
+}
+
+fn print_phase_row(name: &str, s: &PhaseStats) {
+    let acpl = if s.moves > 0 {
+        s.total_loss as f64 / s.moves as f64
+    } else {
+        0.0
+    };
+    println!(
+        "  {:<11} {:>6} {:>6.1} {:>11} {:>8} {:>9}",
+        name, s.moves, acpl, s.by_severity[0], s.by_severity[1], s.by_severity[2]
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_games_take_the_nn_side_from_their_colour() {
+        let doc = parse_json(
+            r#"{"levels":[{"level":2,"games":[
+                {"opening_index":0,"color":"black","outcome":"loss","moves":[{"uci":"e2e4"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let games = games_from_json(&doc, "results.json").unwrap();
+        assert_eq!(games[0].nn_side, Some(Color::Black));
+        assert_eq!(games[0].level, Some(2));
+        assert!(games[0].lost);
+        assert_eq!(games[0].moves.len(), 1);
+    }
+
+    #[test]
+    fn json_games_without_a_colour_are_rejected() {
+        let doc = parse_json(r#"{"levels":[{"level":1,"games":[{"moves":[]}]}]}"#).unwrap();
+        let err = games_from_json(&doc, "results.json").err().unwrap();
+        assert!(err.contains("no NN \"color\""), "{err}");
+    }
+}
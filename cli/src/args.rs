@@ -0,0 +1,12 @@
+//! Command-line argument helpers.
+
+/// Parse the value of `flag`, exiting with an error if it doesn't parse.
+pub fn parse_or_exit<T: std::str::FromStr>(val: &str, flag: &str) -> T {
+    match val.parse() {
+        Ok(v) => v,
+        Err(_) => {
+            eprintln!("Error: invalid value for {flag}: {val}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,25 +1,27 @@
 /// Competition runner: pit an ONNX eval network against baseline bots at multiple levels.
 ///
 /// Usage:
-///   compete <model.onnx> [--level N] [--openings <path>] [--json-output <path>]
+///   compete <model.onnx> [--level N] [--openings <path>] [--json-output <path>] [--pgn-output <path>] [--watch]
 ///
 /// The NN plays all openings x 2 colors per level against increasingly strong baselines.
 /// Scoring: 1 for win, 0.5 for draw, 0 for loss. Must reach 70%.
 /// Models with >10 000 000 parameters are rejected.
-
-use engine::bot::Bot;
+use engine::compete::{
+    pass_points, run_game, score_outcome, select_positions, EngineStats, GameResult, LevelTally,
+    MoveRecord, PASS_THRESHOLD,
+};
+use engine::diagram::{board_diagram, move_squares};
 use engine::game::{GameState, Outcome};
 use engine::nn::count_parameters;
-use engine::openings::load_opening_fens;
-use engine::{format_move, BaselineBot, Color, Level, NnEvalBot, ALL_LEVELS};
+use engine::openings::{load_opening_entries, OpeningEntry};
+use engine::pgn::{quote_tag_value, wrap_movetext};
+use engine::{BaselineBot, Color, Level, NnEvalBot, Square, ALL_LEVELS};
 use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::path::Path;
 use std::time::Instant;
 
 const MAX_PARAMS: u64 = 10_000_000;
-const MAX_PLIES: usize = 500;
-const PASS_THRESHOLD: f64 = 0.70;
 
 // ---------------------------------------------------------------------------
 // Diversity tracking
@@ -101,121 +103,177 @@ impl DiversityTracker {
 }
 
 // ---------------------------------------------------------------------------
-// Move record (for JSON replay output)
+// Move timing summary
 // ---------------------------------------------------------------------------
 
-struct MoveRecord {
-    uci: String,
-    fen: String,
-    side: String, // "white" or "black"
-}
-
-struct GameResult {
-    outcome: Outcome,
-    plies: usize,
-    nn_moves: Vec<String>,
-    starting_fen: String,
-    move_history: Vec<MoveRecord>,
-    reason: String,
+#[derive(Default, Clone)]
+struct TimingSummary {
+    nn_moves: u64,
+    nn_time_ms: f64,
+    nn_max_ms: f64,
+    nn_calls: u64,
+    nn_positions: u64,
+    baseline_moves: u64,
+    baseline_time_ms: f64,
+    baseline_max_ms: f64,
+    baseline_nodes: u64,
 }
 
-// ---------------------------------------------------------------------------
-// Game runner
-// ---------------------------------------------------------------------------
-
-fn run_game(
-    white: &dyn Bot,
-    black: &dyn Bot,
-    starting_fen: Option<&str>,
-    nn_is_white: bool,
-) -> GameResult {
-    let default_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-    let actual_fen = starting_fen.unwrap_or(default_fen);
-
-    let mut game = match GameState::from_fen(actual_fen) {
-        Ok(g) => g,
-        Err(e) => {
-            eprintln!("Warning: bad FEN ({e}), falling back to startpos");
-            GameState::new()
+impl TimingSummary {
+    fn record_game(&mut self, moves: &[MoveRecord]) {
+        for m in moves {
+            match m.stats {
+                EngineStats::Nn { inference_calls, positions } => {
+                    self.nn_moves += 1;
+                    self.nn_time_ms += m.time_ms;
+                    self.nn_max_ms = self.nn_max_ms.max(m.time_ms);
+                    self.nn_calls += inference_calls;
+                    self.nn_positions += positions;
+                }
+                EngineStats::Baseline { nodes } => {
+                    self.baseline_moves += 1;
+                    self.baseline_time_ms += m.time_ms;
+                    self.baseline_max_ms = self.baseline_max_ms.max(m.time_ms);
+                    self.baseline_nodes += nodes;
+                }
+            }
         }
-    };
+    }
 
-    let mut plies = 0;
-    let mut nn_moves: Vec<String> = Vec::new();
-    let mut move_history: Vec<MoveRecord> = Vec::new();
-
-    loop {
-        if game.is_game_over() {
-            let outcome = game.outcome().unwrap_or(Outcome::Draw);
-            let reason = match &outcome {
-                Outcome::Checkmate { .. } => "checkmate",
-                Outcome::Draw => {
-                    if game.is_threefold_repetition() {
-                        "repetition"
-                    } else {
-                        "draw"
-                    }
-                }
-            };
-            return GameResult {
-                outcome,
-                plies,
-                nn_moves,
-                starting_fen: actual_fen.to_string(),
-                move_history,
-                reason: reason.to_string(),
-            };
+    fn nn_avg(&self, total: f64) -> f64 {
+        if self.nn_moves > 0 {
+            total / self.nn_moves as f64
+        } else {
+            0.0
         }
+    }
 
-        if plies >= MAX_PLIES {
-            return GameResult {
-                outcome: Outcome::Draw,
-                plies,
-                nn_moves,
-                starting_fen: actual_fen.to_string(),
-                move_history,
-                reason: "max_plies".to_string(),
-            };
+    fn baseline_avg(&self, total: f64) -> f64 {
+        if self.baseline_moves > 0 {
+            total / self.baseline_moves as f64
+        } else {
+            0.0
         }
+    }
 
-        let side = game.side_to_move();
-        let is_nn_turn =
-            (side == Color::White && nn_is_white) || (side == Color::Black && !nn_is_white);
+    fn report(&self) {
+        println!();
+        println!("--- Move Timing ---");
+        println!(
+            "NN:                  {} moves, avg {:.1}ms, max {:.1}ms, {:.1} calls/move, {:.1} positions/move",
+            self.nn_moves,
+            self.nn_avg(self.nn_time_ms),
+            self.nn_max_ms,
+            self.nn_avg(self.nn_calls as f64),
+            self.nn_avg(self.nn_positions as f64),
+        );
+        println!(
+            "Baseline:            {} moves, avg {:.1}ms, max {:.1}ms, {:.0} nodes/move",
+            self.baseline_moves,
+            self.baseline_avg(self.baseline_time_ms),
+            self.baseline_max_ms,
+            self.baseline_avg(self.baseline_nodes as f64),
+        );
+    }
 
-        let mv = if side == Color::White {
-            white.choose_move(&game)
-        } else {
-            black.choose_move(&game)
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"nn_moves\":{},\"nn_avg_ms\":{:.3},\"nn_max_ms\":{:.3},\"nn_avg_inference_calls\":{:.2},\"nn_avg_positions\":{:.2},\"baseline_moves\":{},\"baseline_avg_ms\":{:.3},\"baseline_max_ms\":{:.3},\"baseline_avg_nodes\":{:.1}}}",
+            self.nn_moves,
+            self.nn_avg(self.nn_time_ms),
+            self.nn_max_ms,
+            self.nn_avg(self.nn_calls as f64),
+            self.nn_avg(self.nn_positions as f64),
+            self.baseline_moves,
+            self.baseline_avg(self.baseline_time_ms),
+            self.baseline_max_ms,
+            self.baseline_avg(self.baseline_nodes as f64),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Live view (--watch)
+// ---------------------------------------------------------------------------
+
+/// Level state shown by `--watch`, redrawn in place after every move.
+struct Watch {
+    level: Level,
+    pass_points: usize,
+    total_games: usize,
+    games_played: usize,
+    score: f64,
+    opening: String,
+    /// Timing of the finished games of this level.
+    timing: TimingSummary,
+    /// Summary lines of the finished positions, newest last.
+    recent: Vec<String>,
+}
+
+impl Watch {
+    fn draw(&self, game: &GameState, moves: &[MoveRecord], nn_is_white: bool) {
+        let mut timing = self.timing.clone();
+        timing.record_game(moves);
+        let last_ply = game.history.len().checked_sub(1);
+        let before = last_ply.and_then(|ply| game.board_at_ply(ply));
+        let highlight: Vec<Square> = match (before, game.history.last()) {
+            (Some(board), Some(&mv)) => move_squares(board, mv).to_vec(),
+            _ => Vec::new(),
         };
 
-        match mv {
-            Some(mv) => {
-                let uci = format_move(mv);
-                let side_str = if side == Color::White { "white" } else { "black" };
-                if is_nn_turn {
-                    nn_moves.push(uci.clone());
-                }
-                game.make_move(mv);
-                let fen = game.board.to_string();
-                move_history.push(MoveRecord {
-                    uci,
-                    fen,
-                    side: side_str.to_string(),
-                });
-                plies += 1;
-            }
-            None => {
-                let winner = !side;
-                return GameResult {
-                    outcome: Outcome::Checkmate { winner },
-                    plies,
-                    nn_moves,
-                    starting_fen: actual_fen.to_string(),
-                    move_history,
-                    reason: "forfeit".to_string(),
-                };
+        let mut out = String::from("\x1b[H\x1b[2J");
+        out.push_str(&format!(
+            "Level {} \u{2014} {}    game {}/{}    {}\n",
+            self.level.value(),
+            self.level.name(),
+            self.games_played + 1,
+            self.total_games,
+            truncate(&self.opening, 40),
+        ));
+        out.push_str(&format!(
+            "NN plays {}    score {:.1}/{} (need {})\n\n",
+            if nn_is_white { "White" } else { "Black" },
+            self.score,
+            self.games_played,
+            self.pass_points,
+        ));
+        out.push_str(&board_diagram(&game.board, Color::White, &highlight));
+        out.push('\n');
+
+        // Numbered SAN, keeping only the last few lines of long games.
+        let start = game.board_at_ply(0).unwrap_or(&game.board).to_string();
+        let movetext = wrap_movetext(&numbered_moves(&start, moves, |_| None));
+        let lines: Vec<&str> = movetext.lines().collect();
+        for line in &lines[lines.len().saturating_sub(4)..] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push('\n');
+
+        out.push_str(&format!(
+            "NN:       {} moves, avg {:.1}ms, max {:.1}ms\n",
+            timing.nn_moves,
+            timing.nn_avg(timing.nn_time_ms),
+            timing.nn_max_ms,
+        ));
+        out.push_str(&format!(
+            "Baseline: {} moves, avg {:.1}ms, max {:.1}ms, {:.0} nodes/move\n",
+            timing.baseline_moves,
+            timing.baseline_avg(timing.baseline_time_ms),
+            timing.baseline_max_ms,
+            timing.baseline_avg(timing.baseline_nodes as f64),
+        ));
+        if !self.recent.is_empty() {
+            out.push('\n');
+            for line in &self.recent[self.recent.len().saturating_sub(5)..] {
+                out.push_str(line);
+                out.push('\n');
             }
         }
+
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
     }
 }
 
@@ -223,11 +281,11 @@ fn run_game(
 // Opening loading & position selection
 // ---------------------------------------------------------------------------
 
-fn load_openings_or_fallback(path: &Path) -> Vec<String> {
-    match load_opening_fens(path) {
-        Ok(fens) => {
-            println!("Loaded {} openings from {}", fens.len(), path.display());
-            fens
+fn load_openings_or_fallback(path: &Path) -> Vec<OpeningEntry> {
+    match load_opening_entries(path) {
+        Ok(entries) => {
+            println!("Loaded {} openings from {}", entries.len(), path.display());
+            entries
         }
         Err(e) => {
             eprintln!("Note: {e} \u{2014} using standard startpos for all games.");
@@ -236,28 +294,48 @@ fn load_openings_or_fallback(path: &Path) -> Vec<String> {
     }
 }
 
-/// Return all openings in file order (deterministic, no sampling).
-fn select_positions(openings: &[String]) -> Vec<String> {
-    if openings.is_empty() {
-        return vec!["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()];
-    }
-    openings.to_vec()
-}
-
 // ---------------------------------------------------------------------------
-// Scoring
+// Per-family breakdown
 // ---------------------------------------------------------------------------
 
-fn score_outcome(outcome: &Outcome, nn_color: Color) -> f64 {
-    match outcome {
-        Outcome::Checkmate { winner } => {
-            if *winner == nn_color {
-                1.0
-            } else {
-                0.0
+/// Points and games per opening family, in order of first appearance.
+#[derive(Default)]
+struct FamilyBreakdown {
+    families: Vec<(String, f64, usize)>,
+}
+
+impl FamilyBreakdown {
+    fn record(&mut self, family: &str, points: f64) {
+        match self.families.iter_mut().find(|(name, _, _)| name == family) {
+            Some((_, total, games)) => {
+                *total += points;
+                *games += 1;
             }
+            None => self.families.push((family.to_string(), points, 1)),
         }
-        Outcome::Draw => 0.5,
+    }
+
+    fn get(&self, family: &str) -> Option<(f64, usize)> {
+        self.families
+            .iter()
+            .find(|(name, _, _)| *name == family)
+            .map(|&(_, points, games)| (points, games))
+    }
+
+    fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .families
+            .iter()
+            .map(|(name, points, games)| {
+                format!(
+                    "{{\"family\":{},\"points\":{:.1},\"games\":{}}}",
+                    json_string_or_null(Some(name)),
+                    points,
+                    games
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
     }
 }
 
@@ -273,7 +351,10 @@ struct LevelResult {
     losses: usize,
     passed: bool,
     elapsed: std::time::Duration,
+    timing: TimingSummary,
+    families: FamilyBreakdown,
     game_jsons: Vec<String>,
+    game_pgns: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -283,12 +364,15 @@ struct LevelResult {
 fn run_level(
     level: Level,
     nn: &NnEvalBot,
-    positions: &[String],
+    positions: &[OpeningEntry],
+    repetition_aware: bool,
+    watching: bool,
 ) -> LevelResult {
-    let baseline = BaselineBot::from_level(level);
+    let mut baseline = BaselineBot::from_level(level);
+    baseline.repetition_aware = repetition_aware;
     let num_positions = positions.len();
     let total_games = num_positions * 2;
-    let pass_points = (total_games as f64 * PASS_THRESHOLD).ceil() as usize;
+    let pass_points = pass_points(total_games);
 
     println!();
     println!(
@@ -307,69 +391,107 @@ fn run_level(
     println!();
 
     let mut diversity = DiversityTracker::new();
-    let mut total_score: f64 = 0.0;
-    let mut wins = 0usize;
-    let mut draws = 0usize;
-    let mut losses = 0usize;
+    let mut timing = TimingSummary::default();
+    let mut families = FamilyBreakdown::default();
+    let mut tally = LevelTally::default();
     let mut game_jsons: Vec<String> = Vec::new();
+    let mut game_pgns: Vec<String> = Vec::new();
+
+    let mut watch = watching.then(|| Watch {
+        level,
+        pass_points,
+        total_games,
+        games_played: 0,
+        score: 0.0,
+        opening: String::new(),
+        timing: TimingSummary::default(),
+        recent: Vec::new(),
+    });
 
     let timer = Instant::now();
 
-    for (pos_idx, fen) in positions.iter().enumerate() {
+    for (pos_idx, opening) in positions.iter().enumerate() {
+        let fen = &opening.fen;
+        if let Some(w) = watch.as_mut() {
+            w.opening = opening.label().unwrap_or_default();
+        }
         // Game A: NN=White vs Baseline=Black
         baseline.reset();
-        let result_a = run_game(nn, &baseline, Some(fen), true);
+        let result_a = run_game(nn, &baseline, Some(fen), true, |game, moves| {
+            if let Some(w) = &watch {
+                w.draw(game, moves, true);
+            }
+            true
+        });
         diversity.record_game(&result_a.nn_moves);
+        timing.record_game(&result_a.move_history);
         let score_a = score_outcome(&result_a.outcome, Color::White);
-        total_score += score_a;
-        match score_a as u32 {
-            1 => wins += 1,
-            0 => losses += 1,
-            _ => draws += 1,
+        tally.record(score_a);
+        families.record(opening.family(), score_a);
+        game_jsons.push(game_to_json(pos_idx, opening, "white", score_a, &result_a));
+        game_pgns.push(game_to_pgn(level, pos_idx, opening, "white", &result_a));
+        if let Some(w) = watch.as_mut() {
+            w.games_played += 1;
+            w.score = tally.score;
+            w.timing = timing.clone();
         }
-        game_jsons.push(game_to_json(pos_idx, "white", score_a, &result_a));
 
         // Game B: Baseline=White vs NN=Black
         baseline.reset();
-        let result_b = run_game(&baseline, nn, Some(fen), false);
+        let result_b = run_game(nn, &baseline, Some(fen), false, |game, moves| {
+            if let Some(w) = &watch {
+                w.draw(game, moves, false);
+            }
+            true
+        });
         diversity.record_game(&result_b.nn_moves);
+        timing.record_game(&result_b.move_history);
         let score_b = score_outcome(&result_b.outcome, Color::Black);
-        total_score += score_b;
-        match score_b as u32 {
-            1 => wins += 1,
-            0 => losses += 1,
-            _ => draws += 1,
-        }
-        game_jsons.push(game_to_json(pos_idx, "black", score_b, &result_b));
+        tally.record(score_b);
+        families.record(opening.family(), score_b);
+        game_jsons.push(game_to_json(pos_idx, opening, "black", score_b, &result_b));
+        game_pgns.push(game_to_pgn(level, pos_idx, opening, "black", &result_b));
 
-        let label_a = match score_a as u32 {
-            1 => "WIN ",
-            0 => "LOSS",
-            _ => "DRAW",
-        };
-        let label_b = match score_b as u32 {
-            1 => "WIN ",
-            0 => "LOSS",
-            _ => "DRAW",
-        };
+        let label_a = LevelTally::label(score_a);
+        let label_b = LevelTally::label(score_b);
 
-        println!(
-            "  Pos {:>2}/{}  W:{} ({}pl)  B:{} ({}pl)  running={:.1}/{:.0}",
+        let line = format!(
+            "  Pos {:>2}/{}  {:<28}  W:{} ({}pl)  B:{} ({}pl)  running={:.1}/{:.0}",
             pos_idx + 1,
             num_positions,
+            truncate(&opening.label().unwrap_or_default(), 28),
             label_a,
             result_a.plies,
             label_b,
             result_b.plies,
-            total_score,
+            tally.score,
             pass_points,
         );
+        match watch.as_mut() {
+            Some(w) => {
+                w.games_played += 1;
+                w.score = tally.score;
+                w.timing = timing.clone();
+                w.recent.push(line);
+            }
+            None => println!("{line}"),
+        }
     }
 
     let elapsed = timer.elapsed();
 
+    if let Some(w) = &watch {
+        // Leave the finished positions on screen under the last board.
+        println!();
+        for line in &w.recent {
+            println!("{line}");
+        }
+    }
+
     diversity.report();
+    timing.report();
 
+    let total_score = tally.score;
     let passed = total_score >= pass_points as f64;
     let pct = total_score / total_games as f64 * 100.0;
 
@@ -397,12 +519,15 @@ fn run_level(
     LevelResult {
         level,
         score: total_score,
-        wins,
-        draws,
-        losses,
+        wins: tally.wins,
+        draws: tally.draws,
+        losses: tally.losses,
         passed,
         elapsed,
+        timing,
+        families,
         game_jsons,
+        game_pgns,
     }
 }
 
@@ -449,6 +574,8 @@ fn print_scorecard(results: &[LevelResult], param_count: u64, total_games: usize
         );
     }
 
+    print_family_breakdown(results);
+
     println!();
     println!("  Parameters: {}", format_num(param_count));
 
@@ -468,6 +595,52 @@ fn print_scorecard(results: &[LevelResult], param_count: u64, total_games: usize
     );
 }
 
+/// Points per opening family, one column per level played.
+fn print_family_breakdown(results: &[LevelResult]) {
+    let Some(first) = results.first() else {
+        return;
+    };
+    if first.families.families.iter().all(|(name, _, _)| name == "Other") {
+        return; // no sections in the openings file: nothing to break down
+    }
+
+    let width = first
+        .families
+        .families
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Opening family".len());
+    println!();
+    let mut header = format!("  {:<width$}", "Opening family");
+    for r in results {
+        header.push_str(&format!("  {:>9}", format!("L{}", r.level.value())));
+    }
+    println!("{header}");
+    for (family, _, _) in &first.families.families {
+        let mut row = format!("  {:<width$}", family);
+        for r in results {
+            let cell = match r.families.get(family) {
+                Some((points, games)) => format!("{:.1}/{}", points, games),
+                None => "-".to_string(),
+            };
+            row.push_str(&format!("  {:>9}", cell));
+        }
+        println!("{row}");
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max - 1).collect();
+        out.push('\u{2026}');
+        out
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Main
 // ---------------------------------------------------------------------------
@@ -476,12 +649,15 @@ fn main() {
     let args: Vec<String> = std::env::args().collect();
 
     if args.len() < 2 || args[1] == "--help" || args[1] == "-h" {
-        eprintln!("Usage: compete <model.onnx> [--level N] [--openings <path>] [--json-output <path>]");
+        eprintln!("Usage: compete <model.onnx> [--level N] [--openings <path>] [--json-output <path>] [--pgn-output <path>] [--repetition-aware] [--watch]");
         eprintln!();
         eprintln!("  model.onnx            ONNX eval network (input: board [1,1540], output: eval [1,1])");
         eprintln!("  --level N             Run only level N (1-4). Omit to run all levels.");
         eprintln!("  --openings <path>     Path to opening book (default: data/openings.txt, all positions used)");
         eprintln!("  --json-output <path>  write per-game JSON results to file (for server integration)");
+        eprintln!("  --pgn-output <path>   write all games as PGN, with per-move time/nodes/score comments");
+        eprintln!("  --repetition-aware    both bots score repeated positions as draws (unofficial)");
+        eprintln!("  --watch               redraw the game in progress after every move (ANSI terminal)");
         eprintln!();
         eprintln!("Levels:");
         for lv in &ALL_LEVELS {
@@ -496,6 +672,9 @@ fn main() {
     let mut openings_path = String::from("data/openings.txt");
     let mut single_level: Option<u8> = None;
     let mut json_output_path: Option<String> = None;
+    let mut pgn_output_path: Option<String> = None;
+    let mut repetition_aware = false;
+    let mut watching = false;
     {
         let mut i = 2;
         while i < args.len() {
@@ -524,6 +703,14 @@ fn main() {
                         i += 1;
                     }
                 }
+                "--pgn-output" => {
+                    if let Some(val) = args.get(i + 1) {
+                        pgn_output_path = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                "--repetition-aware" => repetition_aware = true,
+                "--watch" => watching = true,
                 _ => {}
             }
             i += 1;
@@ -552,7 +739,7 @@ fn main() {
     }
 
     // Load the ONNX Runtime session
-    let nn = match NnEvalBot::load(model_path) {
+    let mut nn = match NnEvalBot::load(model_path) {
         Ok(b) => b,
         Err(e) => {
             eprintln!("Failed to load model: {e}");
@@ -560,6 +747,8 @@ fn main() {
         }
     };
 
+    nn.repetition_aware = repetition_aware;
+
     // Load openings (fixed set, deterministic order)
     let openings = load_openings_or_fallback(Path::new(&openings_path));
     let positions = select_positions(&openings);
@@ -576,7 +765,7 @@ fn main() {
 
     for level in &levels {
         nn.reset_counters();
-        let result = run_level(*level, &nn, &positions);
+        let result = run_level(*level, &nn, &positions, repetition_aware, watching);
         let (calls, positions_evald) = nn.counters();
         let avg_batch = if calls > 0 {
             positions_evald as f64 / calls as f64
@@ -606,7 +795,7 @@ fn main() {
         let best_level = results.iter().rev().find(|r| r.passed).map(|r| r.level.value());
         let levels_json: Vec<String> = results.iter().map(|r| {
             format!(
-                "{{\"level\":{},\"name\":\"{}\",\"score\":{:.1},\"score_pct\":{:.1},\"wins\":{},\"draws\":{},\"losses\":{},\"passed\":{},\"games\":[{}]}}",
+                "{{\"level\":{},\"name\":\"{}\",\"score\":{:.1},\"score_pct\":{:.1},\"wins\":{},\"draws\":{},\"losses\":{},\"passed\":{},\"timing\":{},\"families\":{},\"games\":[{}]}}",
                 r.level.value(),
                 r.level.name(),
                 r.score,
@@ -615,6 +804,8 @@ fn main() {
                 r.draws,
                 r.losses,
                 if r.passed { "true" } else { "false" },
+                r.timing.to_json(),
+                r.families.to_json(),
                 r.game_jsons.join(","),
             )
         }).collect();
@@ -634,6 +825,14 @@ fn main() {
         }
     }
 
+    // Write PGN output if requested
+    if let Some(ref path) = pgn_output_path {
+        let pgn_content: Vec<String> = results.iter().flat_map(|r| r.game_pgns.iter().cloned()).collect();
+        if let Err(e) = std::fs::write(path, pgn_content.join("\n")) {
+            eprintln!("Warning: failed to write PGN output: {e}");
+        }
+    }
+
     // Exit code: 0 if any level passed, 1 if none
     let any_passed = results.iter().any(|r| r.passed);
     if any_passed {
@@ -643,7 +842,20 @@ fn main() {
     }
 }
 
-fn game_to_json(opening_index: usize, nn_color: &str, score: f64, result: &GameResult) -> String {
+fn json_string_or_null(s: Option<&str>) -> String {
+    match s {
+        Some(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('\"', "\\\"")),
+        None => "null".to_string(),
+    }
+}
+
+fn game_to_json(
+    opening_index: usize,
+    opening: &OpeningEntry,
+    nn_color: &str,
+    score: f64,
+    result: &GameResult,
+) -> String {
     let outcome = if score == 1.0 {
         "win"
     } else if score == 0.0 {
@@ -656,18 +868,31 @@ fn game_to_json(opening_index: usize, nn_color: &str, score: f64, result: &GameR
         .move_history
         .iter()
         .map(|m| {
+            let stats = match m.stats {
+                EngineStats::Nn { inference_calls, positions } => {
+                    format!("\"inference_calls\":{},\"positions\":{}", inference_calls, positions)
+                }
+                EngineStats::Baseline { nodes } => format!("\"nodes\":{}", nodes),
+            };
             format!(
-                "{{\"uci\":\"{}\",\"fen\":\"{}\",\"side\":\"{}\"}}",
+                "{{\"uci\":\"{}\",\"san\":\"{}\",\"fen\":\"{}\",\"side\":\"{}\",\"time_ms\":{:.3},\"score\":{},{}}}",
                 m.uci,
+                m.san,
                 m.fen.replace('\"', "\\\""),
                 m.side,
+                m.time_ms,
+                format_score(m.score),
+                stats,
             )
         })
         .collect();
 
     format!(
-        "{{\"opening_index\":{},\"color\":\"{}\",\"outcome\":\"{}\",\"reason\":\"{}\",\"points\":{:.1},\"move_count\":{},\"starting_fen\":\"{}\",\"moves\":[{}]}}",
+        "{{\"opening_index\":{},\"opening_name\":{},\"eco\":{},\"family\":{},\"color\":\"{}\",\"outcome\":\"{}\",\"reason\":\"{}\",\"points\":{:.1},\"move_count\":{},\"starting_fen\":\"{}\",\"moves\":[{}]}}",
         opening_index,
+        json_string_or_null(opening.name.as_deref()),
+        json_string_or_null(opening.eco.as_deref()),
+        json_string_or_null(Some(opening.family())),
         nn_color,
         outcome,
         result.reason,
@@ -678,6 +903,95 @@ fn game_to_json(opening_index: usize, nn_color: &str, score: f64, result: &GameR
     )
 }
 
+fn game_to_pgn(
+    level: Level,
+    opening_index: usize,
+    opening: &OpeningEntry,
+    nn_color: &str,
+    result: &GameResult,
+) -> String {
+    let baseline_name = format!("BaselineBot L{} ({})", level.value(), level.name());
+    let (white, black) = if nn_color == "white" {
+        ("NnEvalBot".to_string(), baseline_name)
+    } else {
+        (baseline_name, "NnEvalBot".to_string())
+    };
+    let result_tag = match result.outcome {
+        Outcome::Checkmate { winner: Color::White } => "1-0",
+        Outcome::Checkmate { winner: Color::Black } => "0-1",
+        Outcome::Draw(_) => "1/2-1/2",
+    };
+
+    let mut pgn = String::new();
+    pgn.push_str(&format!("[Event \"Chess Challenge Level {}\"]\n", level.value()));
+    pgn.push_str("[Site \"compete\"]\n");
+    pgn.push_str(&format!("[Round \"{}.{}\"]\n", opening_index + 1, if nn_color == "white" { 1 } else { 2 }));
+    pgn.push_str(&format!("[White \"{}\"]\n", white));
+    pgn.push_str(&format!("[Black \"{}\"]\n", black));
+    pgn.push_str(&format!("[Result \"{}\"]\n", result_tag));
+    pgn.push_str("[SetUp \"1\"]\n");
+    pgn.push_str(&format!("[FEN \"{}\"]\n", result.starting_fen));
+    if let Some(eco) = &opening.eco {
+        pgn.push_str(&format!("[ECO {}]\n", quote_tag_value(eco)));
+    }
+    if let Some(name) = &opening.name {
+        pgn.push_str(&format!("[Opening {}]\n", quote_tag_value(name)));
+    }
+    pgn.push_str(&format!("[Termination \"{}\"]\n", result.reason));
+    pgn.push('\n');
+
+    let mut tokens = numbered_moves(&result.starting_fen, &result.move_history, |m| {
+        let stats = match m.stats {
+            EngineStats::Nn { inference_calls, positions } => {
+                format!("calls={} positions={}", inference_calls, positions)
+            }
+            EngineStats::Baseline { nodes } => format!("nodes={}", nodes),
+        };
+        Some(format!("{{time={:.1}ms {} score={}}}", m.time_ms, stats, format_score(m.score)))
+    });
+    tokens.push(result_tag.to_string());
+
+    pgn.push_str(&wrap_movetext(&tokens));
+    pgn
+}
+
+/// Movetext tokens for `moves` played from `starting_fen`: move numbers continue from
+/// its fullmove counter, and `comment` may add a token after each move.
+fn numbered_moves(
+    starting_fen: &str,
+    moves: &[MoveRecord],
+    comment: impl Fn(&MoveRecord) -> Option<String>,
+) -> Vec<String> {
+    let mut fields = starting_fen.split_whitespace().skip(1);
+    let mut black_to_move = fields.next() == Some("b");
+    let mut move_number: u32 = fields.nth(3).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+    let mut tokens: Vec<String> = Vec::new();
+    for (i, m) in moves.iter().enumerate() {
+        if !black_to_move {
+            tokens.push(format!("{}.", move_number));
+        } else if i == 0 {
+            tokens.push(format!("{}...", move_number));
+        }
+        tokens.push(m.san.clone());
+        tokens.extend(comment(m));
+
+        if black_to_move {
+            move_number += 1;
+        }
+        black_to_move = !black_to_move;
+    }
+    tokens
+}
+
+/// Scores are written with up to 4 decimals; non-finite network outputs become null.
+fn format_score(score: Option<f64>) -> String {
+    match score {
+        Some(s) if s.is_finite() => ((s * 10_000.0).round() / 10_000.0).to_string(),
+        _ => "null".to_string(),
+    }
+}
+
 fn format_num(n: u64) -> String {
     let s = n.to_string();
     let mut out = String::new();
@@ -689,3 +1003,32 @@ fn format_num(n: u64) -> String {
     }
     out.chars().rev().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::game::STARTPOS;
+
+    fn record(san: &str) -> MoveRecord {
+        MoveRecord {
+            uci: String::new(),
+            san: san.to_string(),
+            fen: String::new(),
+            side: String::new(),
+            time_ms: 0.0,
+            score: None,
+            stats: EngineStats::Baseline { nodes: 0 },
+        }
+    }
+
+    #[test]
+    fn numbering_continues_from_the_starting_position() {
+        let moves = [record("e5"), record("Nf3"), record("Nc6")];
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        let tokens = numbered_moves(fen, &moves, |_| None);
+        assert_eq!(tokens.join(" "), "1... e5 2. Nf3 Nc6");
+
+        let tokens = numbered_moves(STARTPOS, &moves[1..], |m| Some(format!("{{{}}}", m.san)));
+        assert_eq!(tokens.join(" "), "1. Nf3 {Nf3} Nc6 {Nc6}");
+    }
+}
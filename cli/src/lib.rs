@@ -0,0 +1,3 @@
+//! Helpers shared by the command-line tools.
+
+pub mod args;
@@ -1,91 +1,260 @@
-use engine::bot::{Bot, BaselineBot};
+/// Play against a bot in the terminal.
+///
+/// Usage:
+///   cli [--color white|black|random] [--level N] [--model <model.onnx>] [--fen <fen>]
+///
+/// Moves are entered in UCI (e2e4, e7e8q) or SAN (Nf3, O-O). Type `help` during the
+/// game for the other commands.
+use engine::bot::{BaselineBot, Bot};
+use engine::diagram::board_diagram;
+use engine::eval::evaluate;
 use engine::game::{GameState, Outcome};
-use engine::uci::{format_move, parse_file, parse_rank, piece_unicode};
-use engine::{Color, File, Move, Piece, Rank, Square};
+use engine::uci::{format_move, parse_legal_uci_move};
+use engine::{format_san, parse_san, Color, Level, Move, NnEvalBot};
 use std::io::{self, Write};
+use std::path::Path;
 
-fn print_board(game: &GameState) {
-    let board = &game.board;
-    println!("  a b c d e f g h");
-    for rank in (0..8usize).rev() {
-        print!("{} ", rank + 1);
-        for file in 0..8usize {
-            let sq = Square::new(File::index(file), Rank::index(rank));
-            if let Some(piece) = board.piece_on(sq) {
-                let color = if board.colors(Color::White).has(sq) {
-                    Color::White
-                } else {
-                    Color::Black
-                };
-                print!("{} ", piece_unicode(piece, color));
-            } else {
-                print!(". ");
+/// The bot on the other side of the board.
+enum Opponent {
+    Baseline(BaselineBot, Option<Level>),
+    Nn(NnEvalBot),
+}
+
+impl Opponent {
+    fn bot(&self) -> &dyn Bot {
+        match self {
+            Opponent::Baseline(bot, _) => bot,
+            Opponent::Nn(nn) => nn,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Opponent::Baseline(_, Some(level)) => {
+                format!("BaselineBot L{} ({})", level.value(), level.name())
             }
+            Opponent::Baseline(_, None) => "BaselineBot".to_string(),
+            Opponent::Nn(_) => "NnEvalBot".to_string(),
         }
-        println!("{}", rank + 1);
     }
-    println!("  a b c d e f g h");
 }
 
-fn parse_move(input: &str, game: &GameState) -> Option<Move> {
-    let input = input.trim();
-    if input.len() < 4 || input.len() > 5 {
-        return None;
+fn color_name(color: Color) -> &'static str {
+    if color == Color::White {
+        "White"
+    } else {
+        "Black"
     }
+}
 
-    let chars: Vec<char> = input.chars().collect();
+/// Print the board with `bottom`'s pieces at the bottom.
+fn print_board(game: &GameState, bottom: Color) {
+    print!("{}", board_diagram(&game.board, bottom, &[]));
+}
 
-    let from_file = parse_file(chars[0])?;
-    let from_rank = parse_rank(chars[1])?;
-    let to_file = parse_file(chars[2])?;
-    let to_rank = parse_rank(chars[3])?;
+/// Parse a move in UCI or SAN against the current position.
+fn parse_move(input: &str, game: &GameState) -> Option<Move> {
+    parse_legal_uci_move(&game.board, input).or_else(|| parse_san(&game.board, input))
+}
 
-    let from = Square::new(from_file, from_rank);
-    let to = Square::new(to_file, to_rank);
+fn print_help() {
+    println!("Commands:");
+    println!("  <move>   play a move in UCI (e2e4, e7e8q) or SAN (Nf3, O-O)");
+    println!("  undo     take back your last move (and the bot's reply)");
+    println!("  fen      print the current position as FEN");
+    println!("  pgn      print the game so far as PGN");
+    println!("  hint     ask the bot what it would play here");
+    println!("  eval     evaluate the current position");
+    println!("  flip     turn the board around");
+    println!("  resign   give up the game");
+    println!("  quit     exit");
+}
+
+/// The side asked for with `--color`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorChoice {
+    Fixed(Color),
+    Random,
+}
 
-    let promotion = if chars.len() == 5 {
-        match chars[4].to_ascii_lowercase() {
-            'q' => Some(Piece::Queen),
-            'r' => Some(Piece::Rook),
-            'b' => Some(Piece::Bishop),
-            'n' => Some(Piece::Knight),
-            _ => return None,
+impl ColorChoice {
+    fn parse(name: &str) -> Result<ColorChoice, String> {
+        match name.to_lowercase().as_str() {
+            "white" | "w" => Ok(ColorChoice::Fixed(Color::White)),
+            "black" | "b" => Ok(ColorChoice::Fixed(Color::Black)),
+            "random" => Ok(ColorChoice::Random),
+            other => Err(format!(
+                "--color must be white, black or random (got {other})"
+            )),
         }
-    } else {
-        None
-    };
+    }
 
-    let mv = Move { from, to, promotion };
+    /// The side to play, using the low bit of `coin` for a random choice.
+    fn pick(self, coin: u64) -> Color {
+        match self {
+            ColorChoice::Fixed(color) => color,
+            ColorChoice::Random if coin & 1 == 0 => Color::White,
+            ColorChoice::Random => Color::Black,
+        }
+    }
+}
 
-    // Verify the move is legal
-    if game.legal_moves().contains(&mv) {
-        Some(mv)
-    } else {
-        None
+/// Command-line options, without the program name.
+#[derive(Debug, PartialEq)]
+struct Options {
+    color: ColorChoice,
+    level: Option<Level>,
+    model_path: Option<String>,
+    fen: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        color: ColorChoice::Fixed(Color::White),
+        level: None,
+        model_path: None,
+        fen: None,
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--color" => {
+                if let Some(val) = value {
+                    opts.color = ColorChoice::parse(val)?;
+                    i += 1;
+                }
+            }
+            "--level" => {
+                if let Some(val) = value {
+                    let lv = val.parse::<u8>().ok().and_then(Level::new);
+                    opts.level = Some(lv.ok_or("--level must be 1-4")?);
+                    i += 1;
+                }
+            }
+            "--model" => {
+                if let Some(val) = value {
+                    opts.model_path = Some(val.clone());
+                    i += 1;
+                }
+            }
+            "--fen" => {
+                if let Some(val) = value {
+                    opts.fen = Some(val.clone());
+                    i += 1;
+                }
+            }
+            other => return Err(format!("unknown argument {other}")),
+        }
+        i += 1;
+    }
+    if opts.model_path.is_some() && opts.level.is_some() {
+        return Err("--level and --model are mutually exclusive".to_string());
+    }
+    Ok(opts)
+}
+
+/// Take back plies until it is `human`'s move again, at least one of theirs,
+/// and return how many were taken back. When there is nothing of theirs to
+/// take back the game is left as it was and 0 is returned.
+fn take_back(game: &mut GameState, human: Color) -> usize {
+    let mut undone = Vec::new();
+    while let Some(mv) = game.undo_move() {
+        undone.push(mv);
+        if game.side_to_move() == human {
+            break;
+        }
+    }
+    if game.side_to_move() != human {
+        // Restore what was undone.
+        for &mv in undone.iter().rev() {
+            game.make_move(mv);
+        }
+        return 0;
     }
+    undone.len()
 }
 
 fn main() {
-    let mut game = GameState::new();
-    let bot = BaselineBot::default();
-    let human_color = Color::White;
+    let args: Vec<String> = std::env::args().collect();
 
-    println!("Chess vs BaselineBot");
-    println!("You play as White. Enter moves in UCI format (e.g. e2e4, e7e8q).");
-    println!("Type 'quit' to exit.");
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("Usage: cli [--color white|black|random] [--level N] [--model <model.onnx>] [--fen <fen>]");
+        eprintln!();
+        eprintln!("  --color C        side you play (default: white)");
+        eprintln!("  --level N        play the level N baseline, 1-4 (default: BaselineBot::default())");
+        eprintln!("  --model <path>   play against an NnEvalBot instead");
+        eprintln!("  --fen <fen>      start from this position");
+        std::process::exit(1);
+    }
+
+    let Options {
+        color,
+        level,
+        model_path,
+        fen,
+    } = parse_args(&args[1..]).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+    // RandomState is seeded randomly per process: enough for a coin flip.
+    let coin = {
+        use std::hash::{BuildHasher, Hasher};
+        std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish()
+    };
+    let human_color = color.pick(coin);
+
+    let mut game = match &fen {
+        Some(f) => match GameState::from_fen(f) {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => GameState::new(),
+    };
+
+    let opponent = match &model_path {
+        Some(path) => match NnEvalBot::load(Path::new(path)) {
+            Ok(nn) => Opponent::Nn(nn),
+            Err(e) => {
+                eprintln!("Failed to load model: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => match level {
+            Some(lv) => Opponent::Baseline(BaselineBot::from_level(lv), Some(lv)),
+            None => Opponent::Baseline(BaselineBot::default(), None),
+        },
+    };
+
+    println!("Chess vs {}", opponent.name());
+    println!(
+        "You play as {}. Enter moves in UCI (e.g. e2e4, e7e8q) or SAN (e.g. Nf3).",
+        color_name(human_color)
+    );
+    println!("Type 'help' for commands, 'quit' to exit.");
     println!();
 
+    let mut bottom = human_color;
+    let mut show_board = true;
+
     loop {
-        print_board(&game);
-        println!();
+        if show_board {
+            print_board(&game, bottom);
+            println!();
+        }
+        show_board = true;
 
         if game.is_game_over() {
             match game.outcome() {
-                Some(Outcome::Checkmate { winner }) => println!(
-                    "Checkmate! {} wins!",
-                    if winner == Color::White { "White" } else { "Black" }
-                ),
-                Some(Outcome::Draw) => println!("Draw!"),
+                Some(Outcome::Checkmate { winner }) => {
+                    println!("Checkmate! {} wins!", color_name(winner))
+                }
+                Some(Outcome::Draw(reason)) => println!("Draw by {}!", reason.description()),
                 None => println!("Game over."),
             }
             break;
@@ -94,7 +263,7 @@ fn main() {
         let side = game.side_to_move();
 
         if side == human_color {
-            print!("{} to move: ", if side == Color::White { "White" } else { "Black" });
+            print!("{} to move: ", color_name(side));
             io::stdout().flush().unwrap();
 
             let mut input = String::new();
@@ -108,27 +277,75 @@ fn main() {
             }
 
             let trimmed = input.trim();
-            if trimmed == "quit" || trimmed == "exit" {
-                println!("Goodbye!");
-                break;
-            }
-
-            match parse_move(trimmed, &game) {
-                Some(mv) => {
-                    game.make_move(mv);
-                    println!("You played: {}", format_move(mv));
+            show_board = false;
+            match trimmed {
+                "" => continue,
+                "quit" | "exit" => {
+                    println!("Goodbye!");
+                    break;
                 }
-                None => {
-                    println!("Illegal move. Try again (e.g. e2e4).");
-                    continue;
+                "help" => print_help(),
+                "fen" => println!("{}", game.board),
+                "pgn" => {
+                    let (white, black) = if human_color == Color::White {
+                        ("Human".to_string(), opponent.name())
+                    } else {
+                        (opponent.name(), "Human".to_string())
+                    };
+                    print!("{}", game.to_pgn(&[("White", &white), ("Black", &black)]));
+                }
+                "hint" => match opponent.bot().choose_move(&game) {
+                    Some(mv) => println!("Hint: {}", format_san(&game.board, mv)),
+                    None => println!("No legal moves."),
+                },
+                "eval" => {
+                    // Both engines score from the side to move; report from White's view.
+                    let sign = if side == Color::White { 1.0 } else { -1.0 };
+                    println!(
+                        "Static eval: {:+.2} pawns (White's view)",
+                        sign * evaluate(&game.board) as f64 / 100.0
+                    );
+                    if let Opponent::Nn(nn) = &opponent {
+                        match nn.nn_eval(&game) {
+                            Ok(v) => println!("NN eval:     {:+.3} (White's view)", sign * v as f64),
+                            Err(e) => println!("NN eval failed: {e}"),
+                        }
+                    }
                 }
+                "flip" => {
+                    bottom = !bottom;
+                    show_board = true;
+                }
+                "undo" => match take_back(&mut game, human_color) {
+                    0 => println!("Nothing to undo."),
+                    n => {
+                        println!("Took back {} move{}.", n, if n == 1 { "" } else { "s" });
+                        show_board = true;
+                    }
+                },
+                "resign" => {
+                    println!("{} resigns. {} wins!", color_name(human_color), color_name(!human_color));
+                    break;
+                }
+                _ => match parse_move(trimmed, &game) {
+                    Some(mv) => {
+                        let san = format_san(&game.board, mv);
+                        game.make_move(mv);
+                        println!("You played: {} ({})", san, format_move(mv));
+                        show_board = true;
+                    }
+                    None => {
+                        println!("Illegal move or unknown command. Type 'help' for commands.");
+                    }
+                },
             }
         } else {
             println!("Bot is thinking...");
-            match bot.choose_move(&game) {
+            match opponent.bot().choose_move(&game) {
                 Some(mv) => {
+                    let san = format_san(&game.board, mv);
                     game.make_move(mv);
-                    println!("Bot played: {}", format_move(mv));
+                    println!("Bot played: {} ({})", san, format_move(mv));
                 }
                 None => {
                     println!("Bot has no moves.");
@@ -136,6 +353,73 @@ fn main() {
                 }
             }
         }
-        println!();
+        if show_board {
+            println!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::game::STARTPOS;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_color_choices() {
+        let opts = parse_args(&[]).unwrap();
+        assert_eq!(opts.color, ColorChoice::Fixed(Color::White));
+        let opts = parse_args(&args(&["--color", "B"])).unwrap();
+        assert_eq!(opts.color, ColorChoice::Fixed(Color::Black));
+
+        let random = parse_args(&args(&["--color", "random"])).unwrap().color;
+        assert_eq!(random, ColorChoice::Random);
+        assert_eq!(random.pick(2), Color::White);
+        assert_eq!(random.pick(7), Color::Black);
+        assert_eq!(ColorChoice::Fixed(Color::Black).pick(2), Color::Black);
+
+        assert!(parse_args(&args(&["--color", "green"])).is_err());
+    }
+
+    #[test]
+    fn level_and_model_are_exclusive() {
+        let opts = parse_args(&args(&["--level", "3", "--fen", STARTPOS])).unwrap();
+        assert_eq!(opts.level, Level::new(3));
+        assert_eq!(opts.fen.as_deref(), Some(STARTPOS));
+        assert!(parse_args(&args(&["--level", "5"])).is_err());
+        assert!(parse_args(&args(&["--level", "2", "--model", "m.onnx"])).is_err());
+        assert!(parse_args(&args(&["--model", "m.onnx", "--level", "2"])).is_err());
+        let opts = parse_args(&args(&["--model", "m.onnx"])).unwrap();
+        assert_eq!(opts.model_path.as_deref(), Some("m.onnx"));
+        assert!(parse_args(&args(&["--depth", "2"])).is_err());
+    }
+
+    #[test]
+    fn undo_takes_back_to_the_humans_move() {
+        let mut game = GameState::from_fen_and_moves(STARTPOS, &["e2e4", "e7e5"]).unwrap();
+        assert_eq!(take_back(&mut game, Color::White), 2);
+        assert_eq!(game.board.to_string(), STARTPOS);
+
+        // Black to move after White's first move: only that one ply is undone.
+        let mut game = GameState::from_fen_and_moves(STARTPOS, &["e2e4", "e7e5", "g1f3"]).unwrap();
+        assert_eq!(take_back(&mut game, Color::White), 1);
+        assert_eq!(game.history.len(), 2);
+    }
+
+    #[test]
+    fn undo_restores_plies_that_are_not_the_humans() {
+        // Black has not moved yet: the bot's opening move must stay on the board.
+        let mut game = GameState::from_fen_and_moves(STARTPOS, &["e2e4"]).unwrap();
+        let before = game.board.to_string();
+        assert_eq!(take_back(&mut game, Color::Black), 0);
+        assert_eq!(game.board.to_string(), before);
+        assert_eq!(game.history.len(), 1);
+
+        let mut game = GameState::new();
+        assert_eq!(take_back(&mut game, Color::White), 0);
+        assert_eq!(game.board.to_string(), STARTPOS);
     }
 }
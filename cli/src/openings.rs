@@ -0,0 +1,645 @@
+/// Opening suite tooling: import positions from FEN/EPD files, PGN games and move
+/// sequences, validate and deduplicate them, filter by baseline eval, and sample
+/// a balanced suite.
+///
+/// Usage:
+///   openings [inputs...] [--moves "<seq>"]... [--plies N] [--max-eval CP]
+///            [--sample N] [--seed S] [--out <path>]
+///
+/// Inputs:
+///   *.pgn          each game's mainline (up to --plies half-moves) from its start position
+///   anything else  one entry per line: a FEN, an EPD record (`id`/`eco` label the opening),
+///                  or a move sequence from the start position ("1.e4 e5 2.Nf3" or "e2e4 e7e5")
+///
+/// Output: one EPD record per position with `id`, `eco`, `hmvc` and `fmvn` opcodes, readable by
+/// `engine::openings` (and therefore by `compete --openings`).
+use cli::args::parse_or_exit;
+use engine::epd::quote_operand;
+use engine::eval::evaluate;
+use engine::game::{GameState, STARTPOS};
+use engine::openings::{parse_opening_line, section_header};
+use engine::pgn::{parse_pgn, strip_move_number};
+use engine::{format_san, parse_legal_uci_move, parse_san, Board, Piece, Rank, Square};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+const DEFAULT_MAX_EVAL: i32 = 150;
+const DEFAULT_SEED: u64 = 1;
+
+/// A position gathered from one of the inputs, before validation.
+struct Candidate {
+    fen: String,
+    name: Option<String>,
+    eco: Option<String>,
+    /// Section header of the input file the line sat under.
+    family: Option<String>,
+    source: String,
+}
+
+/// A validated position that passed every filter.
+struct Accepted {
+    board: Board,
+    name: Option<String>,
+    eco: Option<String>,
+    family: Option<String>,
+    eval: i32,
+}
+
+/// How many candidates each filter dropped.
+#[derive(Debug, Default, PartialEq)]
+struct Rejected {
+    invalid: usize,
+    terminal: usize,
+    duplicates: usize,
+    unbalanced: usize,
+}
+
+/// Position hash for deduplication. cozy-chess records an en passant file after
+/// every double step, so it only counts here when the capture is legal; otherwise
+/// transpositions ending in a double step would look like new positions.
+fn position_key(board: &Board) -> u64 {
+    let Some(file) = board.en_passant() else {
+        return board.hash();
+    };
+    let target = Square::new(file, Rank::Sixth.relative_to(board.side_to_move()));
+    let mut capture = false;
+    board.generate_moves(|moves| {
+        capture |= moves.piece == Piece::Pawn && moves.to.has(target);
+        capture
+    });
+    if capture {
+        return board.hash();
+    }
+    let fen = board.to_string();
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    fields[3] = "-";
+    fields
+        .join(" ")
+        .parse::<Board>()
+        .map_or(board.hash(), |b| b.hash())
+}
+
+/// Keep the candidates that are legal, not over, not seen before (by position hash)
+/// and within `max_eval` centipawns of balance by the baseline `evaluate`.
+fn filter_candidates(candidates: &[Candidate], max_eval: i32) -> (Vec<Accepted>, Rejected) {
+    let mut seen: HashSet<u64> = HashSet::new();
+    let mut accepted: Vec<Accepted> = Vec::new();
+    let mut rejected = Rejected::default();
+    for c in candidates {
+        let game = match GameState::from_fen(&c.fen) {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("  invalid {}: {e}", c.source);
+                rejected.invalid += 1;
+                continue;
+            }
+        };
+        if game.is_game_over() {
+            rejected.terminal += 1;
+            continue;
+        }
+        if !seen.insert(position_key(&game.board)) {
+            rejected.duplicates += 1;
+            continue;
+        }
+        let eval = evaluate(&game.board);
+        if eval.abs() > max_eval {
+            rejected.unbalanced += 1;
+            continue;
+        }
+        accepted.push(Accepted {
+            board: game.board,
+            name: c.name.clone(),
+            eco: c.eco.clone(),
+            family: c.family.clone(),
+            eval,
+        });
+    }
+    (accepted, rejected)
+}
+
+// ---------------------------------------------------------------------------
+// Seeded sampling
+// ---------------------------------------------------------------------------
+
+/// SplitMix64: small, fast and reproducible across platforms.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Opening family used to balance the sample: the input file's section when
+/// there is one, the first move for bare move lines ("1.e4 c5"), otherwise the
+/// name up to the first ':' or ','.
+fn family(p: &Accepted) -> String {
+    if let Some(family) = &p.family {
+        return family.clone();
+    }
+    let Some(name) = p.name.as_deref() else {
+        return "(unnamed)".to_string();
+    };
+    if name.starts_with("1.") {
+        return name.split_whitespace().next().unwrap_or(name).to_string();
+    }
+    name.split([':', ','])
+        .next()
+        .unwrap_or(name)
+        .trim()
+        .to_string()
+}
+
+/// Pick `n` positions: shuffle each family, then take one from each family in
+/// turn (in a shuffled family order) so no family dominates the suite.
+fn sample_balanced(positions: Vec<Accepted>, n: usize, seed: u64) -> Vec<Accepted> {
+    let mut rng = SplitMix64(seed);
+    let mut families: BTreeMap<String, Vec<Accepted>> = BTreeMap::new();
+    for p in positions {
+        families.entry(family(&p)).or_default().push(p);
+    }
+    let mut buckets: Vec<Vec<Accepted>> = families.into_values().collect();
+    for bucket in &mut buckets {
+        rng.shuffle(bucket);
+    }
+    rng.shuffle(&mut buckets);
+
+    let mut picked = Vec::with_capacity(n);
+    while picked.len() < n && buckets.iter().any(|b| !b.is_empty()) {
+        for bucket in &mut buckets {
+            if picked.len() == n {
+                break;
+            }
+            if let Some(p) = bucket.pop() {
+                picked.push(p);
+            }
+        }
+    }
+    picked
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+/// Play a sequence of SAN or UCI moves (move numbers allowed) from `start`.
+/// Returns the final FEN and the line in SAN, e.g. "1.e4 e5 2.Nf3".
+fn play_sequence(
+    start: &str,
+    tokens: &[&str],
+    max_plies: usize,
+) -> Result<(String, String), String> {
+    let mut game = GameState::from_fen(start)?;
+    let mut line = Vec::new();
+    for token in tokens {
+        let token = strip_move_number(token);
+        if token.is_empty() {
+            continue;
+        }
+        if game.history.len() >= max_plies {
+            break;
+        }
+        let mv = parse_legal_uci_move(&game.board, token)
+            .or_else(|| parse_san(&game.board, token))
+            .ok_or_else(|| format!("illegal or unknown move '{token}'"))?;
+        let number = game.board.fullmove_number();
+        let white = game.side_to_move() == engine::Color::White;
+        let san = format_san(&game.board, mv);
+        if white {
+            line.push(format!("{number}.{san}"));
+        } else if line.is_empty() {
+            line.push(format!("{number}...{san}"));
+        } else {
+            line.push(san);
+        }
+        game.make_move(mv);
+    }
+    Ok((game.board.to_string(), line.join(" ")))
+}
+
+fn import_pgn(
+    path: &Path,
+    text: &str,
+    max_plies: usize,
+    out: &mut Vec<Candidate>,
+    errors: &mut usize,
+) {
+    for (i, game) in parse_pgn(text).into_iter().enumerate() {
+        let source = format!("{} game {}", path.display(), i + 1);
+        let start = game.tag("FEN").unwrap_or(STARTPOS).to_string();
+        let tokens: Vec<&str> = game.moves.iter().map(|s| s.as_str()).collect();
+        match play_sequence(&start, &tokens, max_plies) {
+            Ok((fen, line)) => {
+                let name = match (game.tag("Opening"), game.tag("Variation")) {
+                    (Some(opening), Some(variation)) => format!("{opening}: {variation}"),
+                    (Some(opening), None) => opening.to_string(),
+                    (None, _) => line,
+                };
+                out.push(Candidate {
+                    fen,
+                    name: Some(name),
+                    eco: game.tag("ECO").map(|s| s.to_string()),
+                    family: None,
+                    source,
+                });
+            }
+            Err(e) => {
+                eprintln!("  skip {source}: {e}");
+                *errors += 1;
+            }
+        }
+    }
+}
+
+fn import_lines(
+    path: &Path,
+    text: &str,
+    max_plies: usize,
+    out: &mut Vec<Candidate>,
+    errors: &mut usize,
+) {
+    let mut family = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(name) = section_header(line) {
+            family = Some(name.to_string());
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let source = format!("{}:{}", path.display(), i + 1);
+        if let Some(c) = import_text(line, max_plies, source.clone()) {
+            out.push(Candidate {
+                family: family.clone(),
+                ..c
+            });
+        } else {
+            eprintln!("  skip {source}: not a FEN, EPD or legal move sequence");
+            *errors += 1;
+        }
+    }
+}
+
+/// Interpret one line as FEN/EPD, falling back to a move sequence from startpos.
+fn import_text(line: &str, max_plies: usize, source: String) -> Option<Candidate> {
+    if let Ok(entry) = parse_opening_line(line) {
+        if entry.fen.parse::<Board>().is_ok() {
+            return Some(Candidate {
+                fen: entry.fen,
+                name: entry.name,
+                eco: entry.eco,
+                family: None,
+                source,
+            });
+        }
+    }
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (fen, san_line) = play_sequence(STARTPOS, &tokens, max_plies).ok()?;
+    Some(Candidate {
+        fen,
+        name: Some(san_line),
+        eco: None,
+        family: None,
+        source,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+fn to_epd(p: &Accepted) -> String {
+    let fen = p.board.to_string();
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let mut line = fields[..4].join(" ");
+    if let Some(name) = &p.name {
+        line.push_str(&format!(" id {};", quote_operand(name)));
+    }
+    if let Some(eco) = &p.eco {
+        line.push_str(&format!(" eco {};", quote_operand(eco)));
+    }
+    line.push_str(&format!(" hmvc {}; fmvn {};", fields[4], fields[5]));
+    line
+}
+
+// ---------------------------------------------------------------------------
+// Main
+// ---------------------------------------------------------------------------
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 || args[1] == "--help" || args[1] == "-h" {
+        eprintln!("Usage: openings [inputs...] [--moves \"<seq>\"]... [--plies N] [--max-eval CP] [--sample N] [--seed S] [--out <path>]");
+        eprintln!();
+        eprintln!("  inputs               .pgn files, or files with one FEN / EPD / move sequence per line");
+        eprintln!("  --moves \"<seq>\"      add a move sequence from startpos (SAN or UCI, e.g. \"1.e4 c5 2.Nf3\")");
+        eprintln!(
+            "  --plies N            stop PGN games and sequences after N half-moves (default: all)"
+        );
+        eprintln!("  --max-eval CP        drop positions where |baseline eval| > CP (default: {DEFAULT_MAX_EVAL})");
+        eprintln!("  --sample N           pick N positions, balanced across opening families");
+        eprintln!("  --seed S             seed for --sample (default: {DEFAULT_SEED})");
+        eprintln!("  --out <path>         write the EPD suite to a file (default: stdout)");
+        std::process::exit(1);
+    }
+
+    let mut inputs: Vec<String> = Vec::new();
+    let mut sequences: Vec<String> = Vec::new();
+    let mut max_plies = usize::MAX;
+    let mut max_eval = DEFAULT_MAX_EVAL;
+    let mut sample: Option<usize> = None;
+    let mut seed = DEFAULT_SEED;
+    let mut out_path: Option<String> = None;
+    {
+        let mut i = 1;
+        while i < args.len() {
+            let value = args.get(i + 1);
+            match args[i].as_str() {
+                "--moves" => {
+                    if let Some(val) = value {
+                        sequences.push(val.clone());
+                        i += 1;
+                    }
+                }
+                "--plies" => {
+                    if let Some(val) = value {
+                        max_plies = parse_or_exit(val, "--plies");
+                        i += 1;
+                    }
+                }
+                "--max-eval" => {
+                    if let Some(val) = value {
+                        max_eval = parse_or_exit(val, "--max-eval");
+                        i += 1;
+                    }
+                }
+                "--sample" => {
+                    if let Some(val) = value {
+                        sample = Some(parse_or_exit(val, "--sample"));
+                        i += 1;
+                    }
+                }
+                "--seed" => {
+                    if let Some(val) = value {
+                        seed = parse_or_exit(val, "--seed");
+                        i += 1;
+                    }
+                }
+                "--out" => {
+                    if let Some(val) = value {
+                        out_path = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                arg if !arg.starts_with("--") => inputs.push(arg.to_string()),
+                other => {
+                    eprintln!("Error: unknown flag {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    // Gather candidates
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut parse_errors = 0usize;
+    for input in &inputs {
+        let path = Path::new(input);
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Cannot read {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        };
+        let is_pgn = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pgn"));
+        if is_pgn {
+            import_pgn(path, &text, max_plies, &mut candidates, &mut parse_errors);
+        } else {
+            import_lines(path, &text, max_plies, &mut candidates, &mut parse_errors);
+        }
+    }
+    for (i, seq) in sequences.iter().enumerate() {
+        match import_text(seq, max_plies, format!("--moves #{}", i + 1)) {
+            Some(c) => candidates.push(c),
+            None => {
+                eprintln!("  skip --moves #{}: not a legal move sequence", i + 1);
+                parse_errors += 1;
+            }
+        }
+    }
+
+    // Validate, deduplicate, filter
+    let (accepted, rejected) = filter_candidates(&candidates, max_eval);
+
+    let total_accepted = accepted.len();
+    let suite = match sample {
+        Some(n) => sample_balanced(accepted, n, seed),
+        None => accepted,
+    };
+
+    eprintln!(
+        "Imported:     {} positions ({} unparseable)",
+        candidates.len(),
+        parse_errors
+    );
+    eprintln!("Invalid:      {}", rejected.invalid);
+    eprintln!("Game over:    {}", rejected.terminal);
+    eprintln!("Duplicates:   {}", rejected.duplicates);
+    eprintln!("|eval| > {:<4} {}", max_eval, rejected.unbalanced);
+    eprintln!("Accepted:     {}", total_accepted);
+    if let Some(n) = sample {
+        let families: HashSet<String> = suite.iter().map(family).collect();
+        eprintln!(
+            "Sampled:      {}/{} from {} families (seed {})",
+            suite.len(),
+            n,
+            families.len(),
+            seed
+        );
+    }
+    if !suite.is_empty() {
+        let mean = suite.iter().map(|p| p.eval as f64).sum::<f64>() / suite.len() as f64;
+        eprintln!("Mean eval:    {:+.1}cp (side to move)", mean);
+    }
+
+    let mut output = String::new();
+    for p in &suite {
+        output.push_str(&to_epd(p));
+        output.push('\n');
+    }
+    match out_path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, output) {
+                eprintln!("Failed to write {path}: {e}");
+                std::process::exit(1);
+            }
+            eprintln!("Wrote {} positions to {}", suite.len(), path);
+        }
+        None => print!("{output}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(moves: &str) -> Candidate {
+        import_text(moves, usize::MAX, moves.to_string()).unwrap()
+    }
+
+    fn accepted(name: &str, family: Option<&str>) -> Accepted {
+        Accepted {
+            board: Board::default(),
+            name: Some(name.to_string()),
+            eco: None,
+            family: family.map(str::to_string),
+            eval: 0,
+        }
+    }
+
+    #[test]
+    fn transpositions_collapse_to_one_position() {
+        let candidates = [
+            sequence("1.e4 e5 2.Nf3 Nc6"),
+            sequence("1.Nf3 Nc6 2.e4 e5"),
+            sequence("1.d4 d5"),
+        ];
+        let (accepted, rejected) = filter_candidates(&candidates, DEFAULT_MAX_EVAL);
+        assert_eq!(accepted.len(), 2);
+        assert_eq!(accepted[0].name.as_deref(), Some("1.e4 e5 2.Nf3 Nc6"));
+        assert_eq!(rejected.duplicates, 1);
+
+        // A real en passant chance keeps the positions apart.
+        let fen = |ep: &str| Candidate {
+            fen: format!("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq {ep} 0 3"),
+            name: None,
+            eco: None,
+            family: None,
+            source: ep.to_string(),
+        };
+        let candidates = [fen("d6"), fen("-")];
+        let (accepted, _) = filter_candidates(&candidates, i32::MAX);
+        assert_eq!(accepted.len(), 2);
+    }
+
+    #[test]
+    fn illegal_and_finished_positions_are_rejected() {
+        let candidates = [
+            // Fool's mate, a stalemate and a board without kings.
+            sequence("1.f3 e5 2.g4 Qh4#"),
+            sequence("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+            Candidate {
+                fen: "8/8/8/8/8/8/8/8 w - - 0 1".to_string(),
+                name: None,
+                eco: None,
+                family: None,
+                source: "test".to_string(),
+            },
+        ];
+        let (accepted, rejected) = filter_candidates(&candidates, DEFAULT_MAX_EVAL);
+        assert!(accepted.is_empty());
+        let expected = Rejected {
+            invalid: 1,
+            terminal: 2,
+            ..Rejected::default()
+        };
+        assert_eq!(rejected, expected);
+    }
+
+    #[test]
+    fn unbalanced_positions_are_filtered_out() {
+        let queen_up = sequence("rnb1kbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3");
+        let level = sequence("1.e4 e5");
+        let (accepted, rejected) = filter_candidates(&[queen_up, level], DEFAULT_MAX_EVAL);
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].name.as_deref(), Some("1.e4 e5"));
+        assert!(accepted[0].eval.abs() <= DEFAULT_MAX_EVAL);
+        assert_eq!(rejected.unbalanced, 1);
+    }
+
+    #[test]
+    fn sampling_is_seeded_and_balanced() {
+        let positions = || {
+            let mut positions = Vec::new();
+            for i in 0..12 {
+                positions.push(accepted(&format!("Sicilian {i}"), Some("Semi-Open")));
+            }
+            for i in 0..5 {
+                positions.push(accepted(&format!("Queen's Gambit {i}"), Some("Closed")));
+            }
+            // No section: the family is the first move.
+            for i in 0..4 {
+                positions.push(accepted(&format!("1.b3 line {i}"), None));
+            }
+            positions
+        };
+        let take = |n, seed| -> Vec<String> {
+            let picked = sample_balanced(positions(), n, seed);
+            picked.iter().map(|p| p.name.clone().unwrap()).collect()
+        };
+
+        assert_eq!(take(9, 7), take(9, 7));
+        assert_ne!(take(9, 7), take(9, 8));
+
+        for n in [3, 7, 10, 12] {
+            let picked = sample_balanced(positions(), n, 7);
+            assert_eq!(picked.len(), n);
+            let mut counts = BTreeMap::new();
+            for p in &picked {
+                *counts.entry(family(p)).or_insert(0) += 1;
+            }
+            assert_eq!(
+                counts.keys().collect::<Vec<_>>(),
+                ["1.b3", "Closed", "Semi-Open"]
+            );
+            assert!(
+                counts.values().all(|&c| c <= n.div_ceil(3)),
+                "{n}: {counts:?}"
+            );
+        }
+        // Once the small families run out, the rest comes from the big one.
+        assert_eq!(take(30, 7).len(), 21);
+    }
+
+    #[test]
+    fn sequences_accept_zero_castling_after_a_move_number() {
+        let tokens = [
+            "1.e4", "e5", "2.Nf3", "Nc6", "3.Bc4", "Bc5", "4.", "0-0", "Nf6", "5.0-0-0",
+        ];
+        let (fen, line) = play_sequence(STARTPOS, &tokens[..9], 20).unwrap();
+        assert_eq!(line, "1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.O-O Nf6");
+        assert!(fen.starts_with("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 w kq"));
+        assert!(play_sequence(STARTPOS, &tokens, 20).is_err());
+    }
+
+    #[test]
+    fn pgn_games_with_zero_castling_import() {
+        let pgn = "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 *\n";
+        let mut out = Vec::new();
+        let mut errors = 0;
+        import_pgn(Path::new("test.pgn"), pgn, 20, &mut out, &mut errors);
+        assert_eq!(errors, 0);
+        assert_eq!(out.len(), 1);
+        assert_eq!(
+            out[0].name.as_deref(),
+            Some("1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.O-O")
+        );
+    }
+}
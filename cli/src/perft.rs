@@ -0,0 +1,182 @@
+/// Perft: count legal move-tree leaves to verify move generation and FEN handling.
+///
+/// Usage:
+///   perft [--fen <fen>] [--depth N] [--divide] [--check]
+///   perft --suite [--max-nodes N] [--check]
+///
+/// `--divide` prints the node count below each root move (compare against another engine
+/// to find the faulty move). `--check` additionally verifies hashes, FEN round-trips,
+/// `GameState::make_move`/`undo_move` and repetition counts at every node (much slower).
+/// `--suite` runs the bundled reference positions and exits non-zero on any mismatch.
+use engine::game::{GameState, STARTPOS};
+use engine::perft::{perft, perft_checked, perft_divide, REFERENCE_POSITIONS};
+use engine::{format_move, Board};
+use std::time::Instant;
+
+const DEFAULT_DEPTH: u32 = 4;
+const DEFAULT_MAX_NODES: u64 = 5_000_000;
+
+fn count(game: &GameState, depth: u32, check: bool) -> Result<u64, String> {
+    if check {
+        perft_checked(game, depth)
+    } else {
+        Ok(perft(&game.board, depth))
+    }
+}
+
+fn nps(nodes: u64, secs: f64) -> String {
+    if secs > 0.0 {
+        format!("{:.0}", nodes as f64 / secs)
+    } else {
+        "-".to_string()
+    }
+}
+
+fn run_suite(max_nodes: u64, check: bool) -> bool {
+    let mut all_ok = true;
+    println!(
+        "  {:<12} {:>5} {:>12} {:>12}  {:<6} {:>9}",
+        "Position", "Depth", "Expected", "Nodes", "Result", "Time"
+    );
+    for case in REFERENCE_POSITIONS {
+        let game = GameState::from_fen(case.fen).expect("reference FEN must parse");
+        for &(depth, expected) in case.counts {
+            if expected > max_nodes {
+                continue;
+            }
+            let timer = Instant::now();
+            let result = count(&game, depth, check);
+            let secs = timer.elapsed().as_secs_f64();
+            let (nodes, verdict) = match &result {
+                Ok(n) if *n == expected => (n.to_string(), "ok"),
+                Ok(n) => (n.to_string(), "FAIL"),
+                Err(_) => ("-".to_string(), "ERROR"),
+            };
+            println!(
+                "  {:<12} {:>5} {:>12} {:>12}  {:<6} {:>8.2}s",
+                case.name, depth, expected, nodes, verdict, secs
+            );
+            if let Err(e) = result {
+                println!("    {e}");
+            }
+            all_ok &= verdict == "ok";
+        }
+    }
+    println!();
+    println!("{}", if all_ok { "All perft counts match." } else { "Perft MISMATCH." });
+    all_ok
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("Usage: perft [--fen <fen>] [--depth N] [--divide] [--check]");
+        eprintln!("       perft --suite [--max-nodes N] [--check]");
+        eprintln!();
+        eprintln!("  --fen <fen>      position to count from (default: startpos)");
+        eprintln!("  --depth N        plies to count (default: {DEFAULT_DEPTH})");
+        eprintln!("  --divide         print the node count below each root move");
+        eprintln!("  --check          (overrides --divide) verify hashes, FEN round-trips and GameState::make_move at every node");
+        eprintln!("  --suite          run the bundled reference positions");
+        eprintln!("  --max-nodes N    skip suite entries above N nodes (default: {DEFAULT_MAX_NODES})");
+        std::process::exit(1);
+    }
+
+    let mut fen = STARTPOS.to_string();
+    let mut depth = DEFAULT_DEPTH;
+    let mut divide = false;
+    let mut check = false;
+    let mut suite = false;
+    let mut max_nodes = DEFAULT_MAX_NODES;
+    {
+        let mut i = 1;
+        while i < args.len() {
+            let value = args.get(i + 1);
+            match args[i].as_str() {
+                "--fen" => {
+                    if let Some(val) = value {
+                        fen = val.clone();
+                        i += 1;
+                    }
+                }
+                "--depth" => {
+                    if let Some(val) = value {
+                        depth = val.parse().unwrap_or_else(|_| {
+                            eprintln!("Error: --depth must be a non-negative integer");
+                            std::process::exit(1);
+                        });
+                        i += 1;
+                    }
+                }
+                "--max-nodes" => {
+                    if let Some(val) = value {
+                        max_nodes = val.parse().unwrap_or_else(|_| {
+                            eprintln!("Error: --max-nodes must be a number");
+                            std::process::exit(1);
+                        });
+                        i += 1;
+                    }
+                }
+                "--divide" => divide = true,
+                "--check" => check = true,
+                "--suite" => suite = true,
+                other => {
+                    eprintln!("Error: unknown argument {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    if divide && depth == 0 {
+        eprintln!("Error: --divide needs --depth of at least 1");
+        std::process::exit(1);
+    }
+
+    if suite {
+        let ok = run_suite(max_nodes, check);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    let game = match GameState::from_fen(&fen) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let board: &Board = &game.board;
+    let timer = Instant::now();
+
+    if divide && !check {
+        let mut rows: Vec<(String, u64)> = perft_divide(board, depth)
+            .into_iter()
+            .map(|(mv, n)| (format_move(mv), n))
+            .collect();
+        rows.sort();
+        for (mv, n) in &rows {
+            println!("{mv}: {n}");
+        }
+        let nodes: u64 = rows.iter().map(|(_, n)| n).sum();
+        let secs = timer.elapsed().as_secs_f64();
+        println!();
+        println!("Moves: {}", rows.len());
+        println!("Nodes: {nodes}");
+        println!("Time:  {:.3}s ({} nps)", secs, nps(nodes, secs));
+        return;
+    }
+
+    match count(&game, depth, check) {
+        Ok(nodes) => {
+            let secs = timer.elapsed().as_secs_f64();
+            println!("Nodes: {nodes}");
+            println!("Time:  {:.3}s ({} nps)", secs, nps(nodes, secs));
+        }
+        Err(e) => {
+            eprintln!("Check failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,27 +1,452 @@
-/// Single-move player: given a FEN, make one bot move and output JSON.
+/// Single-move player: given a FEN (and optionally the moves played from it),
+/// make one bot move and output JSON.
 ///
 /// Usage:
-///   play-move <model.onnx> <fen>       # NN model mode
-///   play-move --baseline <fen>          # baseline bot mode
+///   play-move <model.onnx> <fen> [options]               # NN model mode
+///   play-move --baseline <fen> [--level N] [options]      # baseline bot mode
+///   play-move <model.onnx | --baseline> - [options]       # read a JSON request from stdin
+///   play-move --serve [--model <model.onnx>] [--tcp <addr> | --unix <path>]
+///
+/// Options:
+///   --moves e2e4,e7e5,...   moves played since the FEN
+///   --multipv K             also list the top K moves with their scores
+///   --eval-only             print the evaluation of the position instead of moving
+///
+/// The moves are replayed from the FEN so repetition draws are detected. A stdin
+/// request looks like {"fen":"...","moves":["e2e4","e7e5"]} and may also carry
+/// "level", "multipv" and "evalOnly"; all fields are optional (default: start
+/// position, no moves), and the command-line options fill in absent ones.
+///
+/// `--serve` keeps the bots loaded and answers one JSON request per line on
+/// stdin/stdout, or on each connection to a local TCP or Unix socket. Serve requests
+/// may also name the `"bot"` ("nn" or "baseline", default nn when a model is loaded)
+/// and an `"id"` that is echoed back; `"level"` is an error when the NN answers.
+/// `--unix` only replaces an existing file at its path if that file is a socket.
+/// Errors are answered with {"error":"..."} and the server keeps running.
 ///
 /// Output (JSON to stdout):
-///   {"uci":"e2e4","fen":"...after move...","gameOver":false,"outcome":null}
-
+///   {"uci":"e2e4","fen":"...after move...","gameOver":false,"outcome":null,"drawReason":null,
+///    "eval":25,"timeMs":3.2}
+/// With --multipv, a "multipv" array of {"uci","san","score"} (best first) is added.
+/// With --eval-only: {"fen":"...","gameOver":false,"outcome":null,"drawReason":null,"eval":25}
+///
+/// `eval` and scores are from the side to move's perspective: centipawns for
+/// baselines, raw network output for the NN (null when no move was made). With
+/// --eval-only the baseline reports the static `evaluate` score and the NN its
+/// output for the position itself.
 use engine::bot::Bot;
-use engine::game::{GameState, Outcome};
-use engine::{format_move, BaselineBot, Color, NnEvalBot};
+use engine::eval::evaluate;
+use engine::game::{GameState, Outcome, STARTPOS};
+use engine::json::{escape_json, parse_json, Json};
+use engine::{format_move, format_san, BaselineBot, Color, Level, Move, NnEvalBot, ALL_LEVELS};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
 
-fn escape_json(s: &str) -> String {
-    s.replace('\\', "\\\\").replace('"', "\\\"")
+/// A position to move from: a starting FEN plus the UCI moves played since.
+struct Request {
+    fen: String,
+    moves: Vec<String>,
+    /// Serve mode only: "nn" or "baseline".
+    bot: Option<String>,
+    /// Baseline level (default: 4).
+    level: Option<Level>,
+    /// Number of top moves to list (0 = just play the best one).
+    multipv: usize,
+    /// Report the position's evaluation instead of moving.
+    eval_only: bool,
+    /// Serve mode only: echoed back as JSON.
+    id: Option<String>,
+}
+
+/// The bot answering a request.
+enum Player<'a> {
+    Nn(&'a NnEvalBot),
+    Baseline(&'a BaselineBot),
+}
+
+impl Player<'_> {
+    fn bot(&self) -> &dyn Bot {
+        match self {
+            Player::Nn(nn) => *nn,
+            Player::Baseline(b) => *b,
+        }
+    }
+
+    fn last_eval(&self) -> Option<f64> {
+        match self {
+            Player::Nn(nn) => nn.last_score().map(f64::from),
+            Player::Baseline(b) => b.last_score().map(f64::from),
+        }
+    }
+
+    /// Every legal move with its score, best first.
+    fn score_moves(&self, game: &GameState) -> Result<Vec<(Move, f64)>, String> {
+        match self {
+            Player::Nn(nn) => nn
+                .score_moves(game)
+                .map(|scored| scored.into_iter().map(|(mv, s)| (mv, s as f64)).collect())
+                .map_err(|e| format!("NnEvalBot inference error: {e}")),
+            Player::Baseline(b) => Ok(b
+                .score_moves(game)
+                .into_iter()
+                .map(|(mv, s)| (mv, s as f64))
+                .collect()),
+        }
+    }
+
+    /// Evaluation of the position itself, without search.
+    fn static_eval(&self, game: &GameState) -> Result<f64, String> {
+        match self {
+            Player::Nn(nn) => nn
+                .nn_eval(game)
+                .map(f64::from)
+                .map_err(|e| format!("NnEvalBot inference error: {e}")),
+            Player::Baseline(_) => Ok(evaluate(&game.board) as f64),
+        }
+    }
+}
+
+/// A JSON number, or null for missing or non-finite values.
+fn json_number(x: Option<f64>) -> String {
+    x.filter(|x| x.is_finite())
+        .map_or("null".to_string(), |x| x.to_string())
+}
+
+fn parse_request(text: &str) -> Result<Request, String> {
+    let doc = parse_json(text)?;
+    if !matches!(doc, Json::Object(_)) {
+        return Err("request must be a JSON object".to_string());
+    }
+    let fen = match doc.get("fen") {
+        None | Some(Json::Null) => STARTPOS.to_string(),
+        Some(v) => v.as_str().ok_or("\"fen\" must be a string")?.to_string(),
+    };
+    let moves = match doc.get("moves") {
+        None | Some(Json::Null) => Vec::new(),
+        Some(v) => v
+            .as_array()
+            .ok_or("\"moves\" must be an array of UCI strings")?
+            .iter()
+            .map(|m| m.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("\"moves\" must be an array of UCI strings")?,
+    };
+    let bot = match doc.get("bot") {
+        None | Some(Json::Null) => None,
+        Some(v) => match v.as_str() {
+            Some(b @ ("nn" | "baseline")) => Some(b.to_string()),
+            _ => return Err("\"bot\" must be \"nn\" or \"baseline\"".to_string()),
+        },
+    };
+    let level = match doc.get("level") {
+        None | Some(Json::Null) => None,
+        Some(v) => Some(
+            v.as_f64()
+                .filter(|n| n.fract() == 0.0 && (1.0..=4.0).contains(n))
+                .and_then(|n| Level::new(n as u8))
+                .ok_or("\"level\" must be 1-4")?,
+        ),
+    };
+    let multipv = match doc.get("multipv") {
+        None | Some(Json::Null) => 0,
+        Some(v) => v
+            .as_f64()
+            .filter(|n| n.fract() == 0.0 && *n >= 0.0)
+            .ok_or("\"multipv\" must be a non-negative integer")? as usize,
+    };
+    let eval_only = match doc.get("evalOnly") {
+        None | Some(Json::Null) => false,
+        Some(v) => v.as_bool().ok_or("\"evalOnly\" must be a boolean")?,
+    };
+    let id = match doc.get("id") {
+        None => None,
+        Some(Json::String(s)) => Some(format!("\"{}\"", escape_json(s))),
+        Some(Json::Number(n)) => Some(n.to_string()),
+        Some(_) => return Err("\"id\" must be a string or number".to_string()),
+    };
+    Ok(Request {
+        fen,
+        moves,
+        bot,
+        level,
+        multipv,
+        eval_only,
+        id,
+    })
+}
+
+/// Replay the request, let `player` move (unless the game is already over) and
+/// return the response JSON.
+fn respond(player: &Player, req: &Request) -> Result<String, String> {
+    let mut game = GameState::from_fen_and_moves(&req.fen, &req.moves)?;
+    let id = req
+        .id
+        .as_ref()
+        .map_or(String::new(), |id| format!("\"id\":{id},"));
+
+    if req.eval_only {
+        let eval = player.static_eval(&game)?;
+        let (outcome, draw_reason) = outcome_json(&game);
+        return Ok(format!(
+            "{{{}\"fen\":\"{}\",\"gameOver\":{},\"outcome\":{},\"drawReason\":{},\"eval\":{}}}",
+            id,
+            escape_json(&game.board.to_string()),
+            game.is_game_over(),
+            outcome,
+            draw_reason,
+            json_number(Some(eval)),
+        ));
+    }
+
+    let mut uci = None;
+    let mut eval = None;
+    let mut multipv = String::new();
+    let timer = Instant::now();
+    if !game.is_game_over() {
+        let chosen = if req.multipv > 0 {
+            let scored = player.score_moves(&game)?;
+            let lines: Vec<String> = scored
+                .iter()
+                .take(req.multipv)
+                .map(|&(mv, score)| {
+                    format!(
+                        "{{\"uci\":\"{}\",\"san\":\"{}\",\"score\":{}}}",
+                        format_move(mv),
+                        format_san(&game.board, mv),
+                        json_number(Some(score)),
+                    )
+                })
+                .collect();
+            multipv = format!(",\"multipv\":[{}]", lines.join(","));
+            eval = scored.first().map(|&(_, score)| score);
+            scored.first().map(|&(mv, _)| mv)
+        } else {
+            let mv = player.bot().choose_move(&game);
+            eval = player.last_eval();
+            mv
+        };
+        if let Some(mv) = chosen {
+            uci = Some(format_move(mv));
+            game.make_move(mv);
+        }
+    }
+    if uci.is_none() {
+        eval = None;
+    }
+    let time_ms = timer.elapsed().as_secs_f64() * 1000.0;
+    let (outcome, draw_reason) = outcome_json(&game);
+    Ok(format!(
+        "{{{}\"uci\":{},\"fen\":\"{}\",\"gameOver\":{},\"outcome\":{},\"drawReason\":{},\"eval\":{},\"timeMs\":{:.1}{}}}",
+        id,
+        uci.map_or("null".to_string(), |u| format!("\"{u}\"")),
+        escape_json(&game.board.to_string()),
+        game.is_game_over(),
+        outcome,
+        draw_reason,
+        json_number(eval),
+        time_ms,
+        multipv,
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Serve mode
+// ---------------------------------------------------------------------------
+
+/// Bots kept loaded across requests.
+struct Server {
+    nn: Option<NnEvalBot>,
+    /// One baseline per level, indexed by level - 1.
+    baselines: Vec<BaselineBot>,
+}
+
+impl Server {
+    fn handle_line(&self, line: &str) -> String {
+        let error = |id: Option<&String>, e: &str| {
+            let id = id.map_or(String::new(), |id| format!("\"id\":{id},"));
+            format!("{{{}\"error\":\"{}\"}}", id, escape_json(e))
+        };
+        let req = match parse_request(line) {
+            Ok(req) => req,
+            Err(e) => return error(None, &e),
+        };
+        let wants_nn = match req.bot.as_deref() {
+            Some(bot) => bot == "nn",
+            None => self.nn.is_some(),
+        };
+        if wants_nn && req.level.is_some() {
+            return error(req.id.as_ref(), "\"level\" applies to the baseline bot");
+        }
+        let player = match (req.bot.as_deref(), &self.nn) {
+            (Some("nn") | None, Some(nn)) => Player::Nn(nn),
+            (Some("nn"), None) => {
+                return error(req.id.as_ref(), "no model loaded (start with --model)")
+            }
+            _ => {
+                let level = req.level.unwrap_or(Level::new(4).unwrap());
+                let bot = &self.baselines[level.value() as usize - 1];
+                // Requests may come from unrelated games: start each search fresh.
+                bot.reset();
+                Player::Baseline(bot)
+            }
+        };
+        respond(&player, &req).unwrap_or_else(|e| error(req.id.as_ref(), &e))
+    }
+}
+
+/// Answer newline-delimited requests from `reader` until it closes.
+fn serve_stream(server: &Mutex<Server>, reader: impl BufRead, mut writer: impl Write) {
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match server.lock() {
+            Ok(server) => server.handle_line(&line),
+            Err(_) => break,
+        };
+        if writeln!(writer, "{response}").and_then(|_| writer.flush()).is_err() {
+            break;
+        }
+    }
+}
+
+fn serve(args: &[String]) {
+    let mut model_path: Option<String> = None;
+    let mut tcp: Option<String> = None;
+    let mut unix: Option<String> = None;
+    {
+        let mut i = 0;
+        while i < args.len() {
+            let value = args.get(i + 1);
+            match args[i].as_str() {
+                "--serve" => {}
+                "--model" => {
+                    if let Some(val) = value {
+                        model_path = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                "--tcp" => {
+                    if let Some(val) = value {
+                        tcp = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                "--unix" => {
+                    if let Some(val) = value {
+                        unix = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                other => {
+                    eprintln!("Error: unknown argument {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    let nn = model_path.map(|path| match NnEvalBot::load(Path::new(&path)) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Failed to load model: {e}");
+            std::process::exit(1);
+        }
+    });
+    let server = Mutex::new(Server {
+        nn,
+        baselines: ALL_LEVELS.iter().map(|&lv| BaselineBot::from_level(lv)).collect(),
+    });
+
+    // Connections are served on their own threads; the lock serializes searches.
+    if let Some(addr) = tcp {
+        let listener = std::net::TcpListener::bind(&addr).unwrap_or_else(|e| {
+            eprintln!("Cannot listen on {addr}: {e}");
+            std::process::exit(1);
+        });
+        eprintln!("play-move: serving on tcp {addr}");
+        std::thread::scope(|scope| {
+            for stream in listener.incoming().flatten() {
+                let server = &server;
+                scope.spawn(move || {
+                    if let Ok(reader) = stream.try_clone() {
+                        serve_stream(server, BufReader::new(reader), stream);
+                    }
+                });
+            }
+        });
+    } else if let Some(path) = unix {
+        serve_unix(&server, &path);
+    } else {
+        serve_stream(&server, std::io::stdin().lock(), std::io::stdout().lock());
+    }
+}
+
+/// Remove a stale socket left at `path` by an earlier server. Anything else
+/// there is left alone and reported as an error.
+#[cfg(unix)]
+fn remove_stale_socket(path: &str) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            std::fs::remove_file(path).map_err(|e| format!("Cannot remove {path}: {e}"))
+        }
+        Ok(_) => Err(format!("{path} exists and is not a socket; not replacing it")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Cannot inspect {path}: {e}")),
+    }
+}
+
+#[cfg(unix)]
+fn serve_unix(server: &Mutex<Server>, path: &str) {
+    if let Err(e) = remove_stale_socket(path) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    let listener = std::os::unix::net::UnixListener::bind(path).unwrap_or_else(|e| {
+        eprintln!("Cannot listen on {path}: {e}");
+        std::process::exit(1);
+    });
+    eprintln!("play-move: serving on unix socket {path}");
+    std::thread::scope(|scope| {
+        for stream in listener.incoming().flatten() {
+            scope.spawn(move || {
+                if let Ok(reader) = stream.try_clone() {
+                    serve_stream(server, BufReader::new(reader), stream);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn serve_unix(_server: &Mutex<Server>, _path: &str) {
+    eprintln!("Unix sockets are not supported on this platform; use --tcp");
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
+    if args.iter().any(|a| a == "--serve") {
+        serve(&args[1..]);
+        return;
+    }
+
     if args.len() < 3 || args[1] == "--help" || args[1] == "-h" {
-        eprintln!("Usage: play-move <model.onnx> <fen>");
-        eprintln!("       play-move --baseline <fen>");
+        eprintln!("Usage: play-move <model.onnx> <fen> [options]");
+        eprintln!("       play-move --baseline <fen> [--level N] [options]");
+        eprintln!("       play-move <model.onnx | --baseline> - [options]   (JSON request on stdin)");
+        eprintln!("       play-move --serve [--model <model.onnx>] [--tcp <addr> | --unix <path>]");
+        eprintln!();
+        eprintln!("  --moves e2e4,e7e5,...  moves played since the FEN");
+        eprintln!("  --level N              baseline level 1-4 (default: 4)");
+        eprintln!("  --multipv K            also list the top K moves with their scores");
+        eprintln!("  --eval-only            print the evaluation of the position instead of moving");
         std::process::exit(1);
     }
 
@@ -29,10 +454,97 @@ fn main() {
     let fen = &args[2];
     let model_path = if is_baseline { None } else { Some(&args[1]) };
 
+    let mut moves: Vec<String> = Vec::new();
+    let mut level: Option<Level> = None;
+    let mut multipv = 0usize;
+    let mut eval_only = false;
+    {
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--level" => {
+                    if let Some(val) = args.get(i + 1) {
+                        match val.parse::<u8>().ok().and_then(Level::new) {
+                            Some(lv) => level = Some(lv),
+                            None => {
+                                eprintln!("Error: --level must be 1-4");
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+                "--multipv" => {
+                    if let Some(val) = args.get(i + 1) {
+                        multipv = val.parse().unwrap_or_else(|_| {
+                            eprintln!("Error: --multipv must be a non-negative integer");
+                            std::process::exit(1);
+                        });
+                        i += 1;
+                    }
+                }
+                "--eval-only" => eval_only = true,
+                "--moves" => {
+                    if let Some(val) = args.get(i + 1) {
+                        moves = val
+                            .split(',')
+                            .map(|m| m.trim().to_string())
+                            .filter(|m| !m.is_empty())
+                            .collect();
+                        i += 1;
+                    }
+                }
+                other => {
+                    eprintln!("Error: unknown argument {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    let mut request = if fen == "-" {
+        let mut text = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut text) {
+            eprintln!("Failed to read stdin: {e}");
+            std::process::exit(1);
+        }
+        match parse_request(&text) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Invalid request: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        Request {
+            fen: fen.clone(),
+            moves,
+            bot: None,
+            level: None,
+            multipv: 0,
+            eval_only: false,
+            id: None,
+        }
+    };
+    if request.level.is_none() {
+        request.level = level;
+    }
+    if request.multipv == 0 {
+        request.multipv = multipv;
+    }
+    request.eval_only |= eval_only;
+    if model_path.is_some() && request.level.is_some() {
+        eprintln!("Error: --level only applies to --baseline");
+        std::process::exit(1);
+    }
+
     // Load bot
-    let baseline = BaselineBot::default();
+    let baseline = request
+        .level
+        .map_or_else(BaselineBot::default, BaselineBot::from_level);
     let nn: Option<NnEvalBot>;
-    let bot: &dyn Bot = if let Some(path) = model_path {
+    let player = if let Some(path) = model_path {
         nn = Some(match NnEvalBot::load(Path::new(path)) {
             Ok(b) => b,
             Err(e) => {
@@ -40,69 +552,120 @@ fn main() {
                 std::process::exit(1);
             }
         });
-        nn.as_ref().unwrap()
+        Player::Nn(nn.as_ref().unwrap())
     } else {
-        &baseline
+        Player::Baseline(&baseline)
     };
 
-    // Parse FEN
-    let mut game = match GameState::from_fen(fen) {
-        Ok(g) => g,
+    match respond(&player, &request) {
+        Ok(json) => println!("{json}"),
         Err(e) => {
-            eprintln!("Invalid FEN: {e}");
+            eprintln!("{e}");
             std::process::exit(1);
         }
-    };
-
-    // Check if game is already over
-    if game.is_game_over() {
-        let (game_over, outcome) = outcome_json(&game);
-        println!(
-            "{{\"uci\":null,\"fen\":\"{}\",\"gameOver\":{},\"outcome\":{}}}",
-            escape_json(&game.board.to_string()),
-            game_over,
-            outcome,
-        );
-        return;
-    }
-
-    // Choose and make move
-    match bot.choose_move(&game) {
-        Some(mv) => {
-            let uci = format_move(mv);
-            game.make_move(mv);
-            let new_fen = game.board.to_string();
-            let (game_over, outcome) = outcome_json(&game);
-            println!(
-                "{{\"uci\":\"{}\",\"fen\":\"{}\",\"gameOver\":{},\"outcome\":{}}}",
-                uci,
-                escape_json(&new_fen),
-                game_over,
-                outcome,
-            );
-        }
-        None => {
-            let (game_over, outcome) = outcome_json(&game);
-            println!(
-                "{{\"uci\":null,\"fen\":\"{}\",\"gameOver\":{},\"outcome\":{}}}",
-                escape_json(&game.board.to_string()),
-                game_over,
-                outcome,
-            );
-        }
     }
 }
 
-fn outcome_json(game: &GameState) -> (bool, String) {
-    if !game.is_game_over() {
-        return (false, "null".to_string());
-    }
+/// `(outcome, drawReason)` JSON values for the current position.
+fn outcome_json(game: &GameState) -> (String, String) {
     match game.outcome() {
+        None => ("null".to_string(), "null".to_string()),
         Some(Outcome::Checkmate { winner }) => {
             let w = if winner == Color::White { "white" } else { "black" };
-            (true, format!("\"checkmate-{w}\""))
+            (format!("\"checkmate-{w}\""), "null".to_string())
+        }
+        Some(Outcome::Draw(reason)) => ("\"draw\"".to_string(), format!("\"{}\"", reason.name())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Server {
+        Server {
+            nn: None,
+            baselines: ALL_LEVELS.iter().map(|&lv| BaselineBot::from_level(lv)).collect(),
         }
-        Some(Outcome::Draw) => (true, "\"draw\"".to_string()),
-        None => (true, "\"draw\"".to_string()),
+    }
+
+    #[test]
+    fn parses_a_full_request() {
+        let req = parse_request(
+            r#"{"fen":"8/8/8/8/8/8/8/K6k w - - 0 1","moves":["a1a2"],"bot":"baseline",
+                "level":2,"multipv":3,"evalOnly":true,"id":"g1"}"#,
+        )
+        .unwrap();
+        assert_eq!(req.fen, "8/8/8/8/8/8/8/K6k w - - 0 1");
+        assert_eq!(req.moves, ["a1a2"]);
+        assert_eq!(req.bot.as_deref(), Some("baseline"));
+        assert_eq!(req.level, Level::new(2));
+        assert_eq!((req.multipv, req.eval_only), (3, true));
+        assert_eq!(req.id.as_deref(), Some("\"g1\""));
+
+        let empty = parse_request("{}").unwrap();
+        assert_eq!(empty.fen, STARTPOS);
+        assert!(empty.moves.is_empty() && empty.level.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_requests() {
+        assert!(parse_request("{\"fen\":").is_err());
+        assert!(parse_request("[1,2]").is_err());
+        assert!(parse_request(r#"{"moves":"e2e4"}"#).is_err());
+        assert!(parse_request(r#"{"level":7}"#).is_err());
+        assert!(parse_request(r#"{"bot":"stockfish"}"#).is_err());
+    }
+
+    #[test]
+    fn responds_with_a_legal_move() {
+        let bot = BaselineBot::from_level(Level::new(1).unwrap());
+        let req = parse_request(r#"{"moves":["e2e4","e7e5"]}"#).unwrap();
+        let json = respond(&Player::Baseline(&bot), &req).unwrap();
+        let doc = parse_json(&json).unwrap();
+        let uci = doc.get("uci").and_then(Json::as_str).unwrap();
+        let game = GameState::from_fen_and_moves(STARTPOS, &["e2e4", "e7e5", uci]).unwrap();
+        assert_eq!(doc.get("fen").and_then(Json::as_str), Some(&*game.board.to_string()));
+        assert_eq!(doc.get("gameOver").and_then(Json::as_bool), Some(false));
+    }
+
+    #[test]
+    fn serve_answers_each_line() {
+        let server = server();
+        let ok = server.handle_line(r#"{"level":1,"id":7}"#);
+        let doc = parse_json(&ok).unwrap();
+        assert_eq!(doc.get("id").and_then(Json::as_f64), Some(7.0));
+        assert!(doc.get("uci").and_then(Json::as_str).is_some());
+
+        let error = |line: &str| {
+            let doc = parse_json(&server.handle_line(line)).unwrap();
+            doc.get("error").and_then(Json::as_str).map(str::to_string)
+        };
+        assert!(error("not json").is_some());
+        let illegal = error(r#"{"moves":["e2e5"],"id":"x"}"#).unwrap();
+        assert!(illegal.contains("e2e5"), "{illegal}");
+        assert!(error(r#"{"bot":"stockfish"}"#).unwrap().contains("\"bot\""));
+        assert!(error(r#"{"bot":"nn"}"#).unwrap().contains("no model loaded"));
+        assert!(error(r#"{"bot":"nn","level":2}"#).unwrap().contains("\"level\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_stale_sockets_are_removed() {
+        let dir = std::env::temp_dir().join(format!("play-move-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("not-a-socket");
+        std::fs::write(&file, "keep me").unwrap();
+        let file = file.to_str().unwrap();
+        assert!(remove_stale_socket(file).is_err());
+        assert_eq!(std::fs::read_to_string(file).unwrap(), "keep me");
+
+        let socket = dir.join("sock");
+        let socket = socket.to_str().unwrap();
+        drop(std::os::unix::net::UnixListener::bind(socket).unwrap());
+        assert!(remove_stale_socket(socket).is_ok());
+        assert!(std::fs::symlink_metadata(socket).is_err());
+        assert!(remove_stale_socket(socket).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,349 @@
+/// Tactical test-suite runner: play one move per EPD position and check it against
+/// the `bm` (best move) and `am` (avoid move) opcodes.
+///
+/// Usage:
+///   suite <positions.epd>... [--model <model.onnx> | --level N] [--depth N] [--time-ms T]
+///         [--failed-only]
+///
+/// A position is solved when the chosen move is one of its `bm` moves (if any) and none
+/// of its `am` moves (if any). Moves may be written in SAN or UCI.
+///
+/// Baseline bots search to their level depth, or `--depth N`. With `--time-ms T` they
+/// deepen iteratively (1, 2, ...) until the next iteration would likely overrun T.
+/// T is a soft budget: the last iteration started is always finished.
+/// The NN bot always searches depth 1, so the limits are rejected with `--model`.
+use engine::bot::Bot;
+use engine::epd::{parse_epd, Epd};
+use engine::game::GameState;
+use engine::{format_san, BaselineBot, Level, Move, NnEvalBot};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const DEFAULT_LEVEL: u8 = 4;
+const MAX_ITERATIVE_DEPTH: u32 = 32;
+
+/// One suite position with its expected and forbidden moves resolved.
+struct Position {
+    id: String,
+    game: GameState,
+    best: Vec<Move>,
+    avoid: Vec<Move>,
+}
+
+impl Position {
+    /// Parse one EPD record; `default_id` names it when it has no `id`.
+    fn parse(line: &str, default_id: String) -> Result<Position, String> {
+        let epd: Epd = parse_epd(line)?;
+        let game = GameState::from_fen(&epd.to_fen())?;
+        let best = epd.moves("bm", &game.board)?;
+        let avoid = epd.moves("am", &game.board)?;
+        if best.is_empty() && avoid.is_empty() {
+            return Err("position has neither bm nor am".to_string());
+        }
+        Ok(Position {
+            id: epd.id().map_or(default_id, |s| s.to_string()),
+            game,
+            best,
+            avoid,
+        })
+    }
+
+    /// One of the `bm` moves (if any) and none of the `am` moves.
+    fn solved_by(&self, mv: Option<Move>) -> bool {
+        mv.is_some_and(|m| {
+            (self.best.is_empty() || self.best.contains(&m)) && !self.avoid.contains(&m)
+        })
+    }
+}
+
+enum Solver {
+    Nn(NnEvalBot),
+    Baseline {
+        bot: BaselineBot,
+        depth: u32,
+        time_limit: Option<Duration>,
+    },
+}
+
+impl Solver {
+    /// Choose a move, returning it with the depth actually searched.
+    fn solve(&mut self, game: &GameState) -> (Option<Move>, u32) {
+        match self {
+            Solver::Nn(nn) => (nn.choose_move(game), 1),
+            Solver::Baseline {
+                bot,
+                depth,
+                time_limit: None,
+            } => {
+                bot.reset();
+                bot.depth = *depth;
+                (bot.choose_move(game), *depth)
+            }
+            Solver::Baseline {
+                bot,
+                depth,
+                time_limit: Some(limit),
+            } => {
+                bot.reset();
+                bot.search_within(game, *depth, *limit)
+            }
+        }
+    }
+}
+
+fn load_positions(path: &Path) -> Result<Vec<Position>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut positions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let location = format!("{}:{}", path.display(), i + 1);
+        let position =
+            Position::parse(line, location.clone()).map_err(|e| format!("{location}: {e}"))?;
+        positions.push(position);
+    }
+    Ok(positions)
+}
+
+fn san_list(game: &GameState, moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|&m| format_san(&game.board, m))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 || args[1] == "--help" || args[1] == "-h" {
+        eprintln!("Usage: suite <positions.epd>... [--model <model.onnx> | --level N] [--depth N] [--time-ms T] [--failed-only]");
+        eprintln!();
+        eprintln!("  --model <path>   solve with an NnEvalBot (depth 1)");
+        eprintln!("  --level N        solve with the level N baseline (default: {DEFAULT_LEVEL})");
+        eprintln!("  --depth N        baseline search depth (default: the level's depth)");
+        eprintln!("  --time-ms T      soft per-position time budget; baselines deepen iteratively up to --depth");
+        eprintln!("  --failed-only    only list positions that were not solved");
+        std::process::exit(1);
+    }
+
+    let mut inputs: Vec<String> = Vec::new();
+    let mut model_path: Option<String> = None;
+    let mut level = Level::new(DEFAULT_LEVEL).unwrap();
+    let mut depth: Option<u32> = None;
+    let mut time_limit: Option<Duration> = None;
+    let mut failed_only = false;
+    let mut level_given = false;
+    {
+        let mut i = 1;
+        while i < args.len() {
+            let value = args.get(i + 1);
+            match args[i].as_str() {
+                "--model" => {
+                    if let Some(val) = value {
+                        model_path = Some(val.clone());
+                        i += 1;
+                    }
+                }
+                "--level" => {
+                    if let Some(val) = value {
+                        match val.parse::<u8>().ok().and_then(Level::new) {
+                            Some(lv) => {
+                                level = lv;
+                                level_given = true;
+                            }
+                            None => {
+                                eprintln!("Error: --level must be 1-4");
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+                "--depth" => {
+                    if let Some(val) = value {
+                        match val.parse::<u32>() {
+                            Ok(d) if d >= 1 => depth = Some(d),
+                            _ => {
+                                eprintln!("Error: --depth must be a positive integer");
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+                "--time-ms" => {
+                    if let Some(val) = value {
+                        match val.parse::<u64>() {
+                            Ok(ms) => time_limit = Some(Duration::from_millis(ms)),
+                            Err(_) => {
+                                eprintln!("Error: --time-ms must be a number of milliseconds");
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+                "--failed-only" => failed_only = true,
+                arg if !arg.starts_with("--") => inputs.push(arg.to_string()),
+                other => {
+                    eprintln!("Error: unknown flag {other}");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    if model_path.is_some() && (level_given || depth.is_some() || time_limit.is_some()) {
+        eprintln!(
+            "Error: --level, --depth and --time-ms apply to baselines; the NN searches depth 1"
+        );
+        std::process::exit(1);
+    }
+
+    let mut positions = Vec::new();
+    for input in &inputs {
+        match load_positions(Path::new(input)) {
+            Ok(p) => positions.extend(p),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if positions.is_empty() {
+        eprintln!("No positions to solve.");
+        std::process::exit(1);
+    }
+
+    let mut solver = match &model_path {
+        Some(path) => match NnEvalBot::load(Path::new(path)) {
+            Ok(nn) => {
+                println!("Solver: NnEvalBot {} (depth 1)", path);
+                Solver::Nn(nn)
+            }
+            Err(e) => {
+                eprintln!("Failed to load model: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let depth = depth.unwrap_or(if time_limit.is_some() {
+                MAX_ITERATIVE_DEPTH
+            } else {
+                level.depth()
+            });
+            println!(
+                "Solver: BaselineBot L{} ({}), {}",
+                level.value(),
+                level.name(),
+                match time_limit {
+                    Some(t) => format!("up to depth {} within {}ms per position", depth, t.as_millis()),
+                    None => format!("depth {}", depth),
+                },
+            );
+            Solver::Baseline {
+                bot: BaselineBot::from_level(level),
+                depth,
+                time_limit,
+            }
+        }
+    };
+    println!();
+
+    let mut solved = 0usize;
+    let mut total_time = Duration::ZERO;
+    for (i, pos) in positions.iter().enumerate() {
+        let timer = Instant::now();
+        let (mv, reached) = solver.solve(&pos.game);
+        let elapsed = timer.elapsed();
+        total_time += elapsed;
+
+        let ok = pos.solved_by(mv);
+        if ok {
+            solved += 1;
+        }
+        if ok && failed_only {
+            continue;
+        }
+
+        let chosen = mv.map_or("(none)".to_string(), |m| format_san(&pos.game.board, m));
+        let mut expected = String::new();
+        if !pos.best.is_empty() {
+            expected.push_str(&format!("bm {}", san_list(&pos.game, &pos.best)));
+        }
+        if !pos.avoid.is_empty() {
+            if !expected.is_empty() {
+                expected.push_str("; ");
+            }
+            expected.push_str(&format!("am {}", san_list(&pos.game, &pos.avoid)));
+        }
+        println!(
+            "  {:>4}  {:<6}  {:<24}  played {:<8}  {:<20}  d{:<2} {:>8.1}ms",
+            i + 1,
+            if ok { "SOLVED" } else { "FAILED" },
+            pos.id,
+            chosen,
+            expected,
+            reached,
+            elapsed.as_secs_f64() * 1000.0,
+        );
+    }
+
+    println!();
+    println!(
+        "Solved {}/{} ({:.1}%) in {:.1}s",
+        solved,
+        positions.len(),
+        solved as f64 / positions.len() as f64 * 100.0,
+        total_time.as_secs_f64(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::parse_legal_uci_move;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+
+    fn play(pos: &Position, uci: &str) -> bool {
+        pos.solved_by(parse_legal_uci_move(&pos.game.board, uci))
+    }
+
+    #[test]
+    fn best_moves_must_be_played() {
+        let pos = Position::parse(&format!("{START} bm e4 d4; id \"open\";"), "x".into()).unwrap();
+        assert_eq!(pos.id, "open");
+        assert!(play(&pos, "e2e4"));
+        assert!(play(&pos, "d2d4"));
+        assert!(!play(&pos, "g1f3"));
+        assert!(!pos.solved_by(None));
+    }
+
+    #[test]
+    fn avoid_moves_must_not_be_played() {
+        let pos = Position::parse(&format!("{START} am f3;"), "suite.epd:3".into()).unwrap();
+        assert_eq!(pos.id, "suite.epd:3");
+        assert!(play(&pos, "e2e4"));
+        assert!(!play(&pos, "f2f3"));
+    }
+
+    #[test]
+    fn best_and_avoid_combine() {
+        let pos = Position::parse(&format!("{START} bm e4 f3; am f3;"), "x".into()).unwrap();
+        assert!(play(&pos, "e2e4"));
+        assert!(!play(&pos, "f2f3"));
+        assert!(!play(&pos, "d2d4"));
+    }
+
+    #[test]
+    fn positions_need_bm_or_am() {
+        assert!(Position::parse(&format!("{START} id \"none\";"), "x".into()).is_err());
+        assert!(Position::parse(&format!("{START} bm Ke2;"), "x".into()).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use cozy_chess::{Board, Move};
+
+use crate::eval::{game_phase, piece_value};
+use crate::search::{best_move_with_scores_enhanced, capture_moves, SearchContext, MATE_SCORE};
+
+/// Scores are clamped to this many centipawns before computing the loss, so a
+/// missed mate counts as a large but finite drop.
+const SCORE_CAP: i32 = 1500;
+
+/// Minimum centipawn loss for each severity.
+pub const INACCURACY_CP: i32 = 50;
+pub const MISTAKE_CP: i32 = 100;
+pub const BLUNDER_CP: i32 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Severity {
+    /// Classify a centipawn loss; `None` below the inaccuracy threshold.
+    pub fn from_loss(loss: i32) -> Option<Severity> {
+        if loss >= BLUNDER_CP {
+            Some(Severity::Blunder)
+        } else if loss >= MISTAKE_CP {
+            Some(Severity::Mistake)
+        } else if loss >= INACCURACY_CP {
+            Some(Severity::Inaccuracy)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Inaccuracy => "inaccuracy",
+            Severity::Mistake => "mistake",
+            Severity::Blunder => "blunder",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl Phase {
+    /// Opening while in the first ten moves with most pieces still on the board,
+    /// endgame once `eval::game_phase` drops to a third of the starting material.
+    pub fn of(board: &Board) -> Phase {
+        let phase = game_phase(board);
+        if phase <= 85 {
+            Phase::Endgame
+        } else if board.fullmove_number() <= 10 && phase >= 200 {
+            Phase::Opening
+        } else {
+            Phase::Middlegame
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Opening => "opening",
+            Phase::Middlegame => "middlegame",
+            Phase::Endgame => "endgame",
+        }
+    }
+}
+
+/// Why a move lost value, checked in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Motif {
+    /// The best move led to a forced mate and the played move did not.
+    MissedMate,
+    /// After the played move the opponent can win material with a capture.
+    HangingPiece,
+    /// The best move was a capture and the played move captured nothing.
+    MissedCapture,
+    Other,
+}
+
+impl Motif {
+    pub fn name(self) -> &'static str {
+        match self {
+            Motif::MissedMate => "missed mate",
+            Motif::HangingPiece => "hanging piece",
+            Motif::MissedCapture => "missed capture",
+            Motif::Other => "other",
+        }
+    }
+}
+
+/// Deep-search verdict on one played move. Scores are centipawns from the
+/// mover's perspective.
+#[derive(Debug, Clone)]
+pub struct MoveAnalysis {
+    pub played: Move,
+    pub played_score: i32,
+    pub best: Move,
+    pub best_score: i32,
+    /// Centipawns lost against the best move (0 when the played move is best).
+    pub loss: i32,
+    pub severity: Option<Severity>,
+    pub motif: Motif,
+    pub phase: Phase,
+}
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - 1000
+}
+
+fn is_capture(board: &Board, mv: Move) -> bool {
+    match board.piece_on(mv.to) {
+        Some(_) => board.color_on(mv.to) != Some(board.side_to_move()),
+        // En passant: a pawn changing file onto an empty square
+        None => {
+            board.piece_on(mv.from) == Some(cozy_chess::Piece::Pawn)
+                && mv.from.file() != mv.to.file()
+        }
+    }
+}
+
+/// True if, after `mv`, the opponent has a capture that wins material: taking a
+/// piece worth more than the capturer, or one the mover can't recapture.
+pub fn leaves_piece_hanging(board: &Board, mv: Move) -> bool {
+    let mut after = board.clone();
+    after.play_unchecked(mv);
+    let mover = board.side_to_move();
+    capture_moves(&after).into_iter().any(|reply| {
+        if after.color_on(reply.to) != Some(mover) {
+            return false;
+        }
+        let victim = after.piece_on(reply.to).map_or(0, piece_value);
+        let attacker = after.piece_on(reply.from).map_or(0, piece_value);
+        if victim > attacker {
+            return true;
+        }
+        let mut replied = after.clone();
+        replied.play_unchecked(reply);
+        !capture_moves(&replied).iter().any(|m| m.to == reply.to)
+    })
+}
+
+fn motif(board: &Board, played: Move, best: Move, best_score: i32, played_score: i32) -> Motif {
+    if is_mate_score(best_score) && best_score > 0 && !(is_mate_score(played_score) && played_score > 0) {
+        Motif::MissedMate
+    } else if leaves_piece_hanging(board, played) {
+        Motif::HangingPiece
+    } else if is_capture(board, best) && !is_capture(board, played) {
+        Motif::MissedCapture
+    } else {
+        Motif::Other
+    }
+}
+
+/// Re-search `board` to `depth` with the enhanced baseline search and judge the
+/// move that was actually played. Returns `None` if `played` is not legal.
+pub fn analyze_move(
+    ctx: &mut SearchContext,
+    board: &Board,
+    played: Move,
+    depth: u32,
+) -> Option<MoveAnalysis> {
+    let scored = best_move_with_scores_enhanced(ctx, board, depth.max(1));
+    let played_score = scored.iter().find(|(m, _)| *m == played)?.1;
+    let &(best, best_score) = scored.iter().max_by_key(|(_, s)| *s)?;
+
+    let loss = (best_score.clamp(-SCORE_CAP, SCORE_CAP)
+        - played_score.clamp(-SCORE_CAP, SCORE_CAP))
+    .max(0);
+    let severity = if best == played {
+        None
+    } else {
+        Severity::from_loss(loss)
+    };
+    let motif = match severity {
+        Some(_) => motif(board, played, best, best_score, played_score),
+        None => Motif::Other,
+    };
+    Some(MoveAnalysis {
+        played,
+        played_score,
+        best,
+        best_score,
+        loss,
+        severity,
+        motif,
+        phase: Phase::of(board),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci::parse_uci_move;
+
+    fn analyze(fen: &str, uci: &str, depth: u32) -> MoveAnalysis {
+        let board: Board = fen.parse().unwrap();
+        let mut ctx = SearchContext::new();
+        analyze_move(&mut ctx, &board, parse_uci_move(uci).unwrap(), depth).unwrap()
+    }
+
+    #[test]
+    fn severity_thresholds() {
+        assert_eq!(Severity::from_loss(49), None);
+        assert_eq!(Severity::from_loss(50), Some(Severity::Inaccuracy));
+        assert_eq!(Severity::from_loss(100), Some(Severity::Mistake));
+        assert_eq!(Severity::from_loss(300), Some(Severity::Blunder));
+    }
+
+    #[test]
+    fn phase_buckets() {
+        assert_eq!(Phase::of(&Board::default()), Phase::Opening);
+        let endgame: Board = "8/5k2/8/8/8/8/2R2K2/8 w - - 0 50".parse().unwrap();
+        assert_eq!(Phase::of(&endgame), Phase::Endgame);
+    }
+
+    #[test]
+    fn missed_mate_is_a_blunder() {
+        // Back-rank mate with Ra8#; Kf1 wastes it
+        let a = analyze("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "g1f1", 3);
+        assert_eq!(a.best, parse_uci_move("a1a8").unwrap());
+        assert_eq!(a.severity, Some(Severity::Blunder));
+        assert_eq!(a.motif, Motif::MissedMate);
+    }
+
+    #[test]
+    fn hanging_queen_is_flagged() {
+        // Qg4?? walks into ...Bxg4
+        let board: Board = "rnbqkbnr/ppp2ppp/8/3pp3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+            .parse()
+            .unwrap();
+        assert!(leaves_piece_hanging(&board, parse_uci_move("d1g4").unwrap()));
+        assert!(!leaves_piece_hanging(&board, parse_uci_move("b1c3").unwrap()));
+        let a = analyze(
+            "rnbqkbnr/ppp2ppp/8/3pp3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "d1g4",
+            2,
+        );
+        assert_eq!(a.severity, Some(Severity::Blunder));
+        assert_eq!(a.motif, Motif::HangingPiece);
+    }
+
+    #[test]
+    fn best_move_is_never_flagged() {
+        let a = analyze("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "a1a8", 3);
+        assert_eq!(a.loss, 0);
+        assert_eq!(a.severity, None);
+    }
+}
@@ -1,19 +1,45 @@
 use cozy_chess::Move;
+use std::time::{Duration, Instant};
 
 use crate::game::GameState;
 use crate::search::{
-    best_move_with_scores_classic, best_move_with_scores_enhanced, SearchContext,
+    best_move_with_scores_classic, best_move_with_scores_classic_in_game,
+    best_move_with_scores_enhanced, best_move_with_scores_enhanced_in_game, nodes_searched,
+    SearchContext,
 };
 
 pub trait Bot {
     fn choose_move(&self, game: &GameState) -> Option<Move>;
+
+    /// Choose a move with `time` left on the mover's clock. Bots that don't
+    /// budget their time search as usual.
+    fn choose_move_timed(&self, game: &GameState, _time: &TimeLeft) -> Option<Move> {
+        self.choose_move(game)
+    }
+}
+
+/// The mover's clock when a bot is asked for a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLeft {
+    pub remaining: Duration,
+    /// Added to the clock once the move is made.
+    pub increment: Duration,
+}
+
+impl TimeLeft {
+    /// Time to spend on one move: a 30th of the clock plus most of the
+    /// increment, never more than half of what is left.
+    pub fn move_budget(&self) -> Duration {
+        let budget = self.remaining / 30 + self.increment * 3 / 4;
+        budget.min(self.remaining / 2)
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Level system
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Level {
     value: u8,
 }
@@ -78,8 +104,15 @@ pub struct BaselineBot {
     pub depth: u32,
     /// true = adds TT, PVS, NMP to the search
     pub enhanced: bool,
+    /// Score positions that repeat the game history (or the search path) as
+    /// draws. Off for the official levels.
+    pub repetition_aware: bool,
     /// Shared search context for enhanced mode (persists across moves)
     ctx: std::cell::RefCell<SearchContext>,
+    /// Nodes visited by the most recent `choose_move` call.
+    last_nodes: std::cell::Cell<u64>,
+    /// Best root score (centipawns, mover's perspective) of the most recent search.
+    last_score: std::cell::Cell<Option<i32>>,
 }
 
 impl Default for BaselineBot {
@@ -87,7 +120,10 @@ impl Default for BaselineBot {
         BaselineBot {
             depth: 4,
             enhanced: true,
+            repetition_aware: false,
             ctx: std::cell::RefCell::new(SearchContext::new()),
+            last_nodes: std::cell::Cell::new(0),
+            last_score: std::cell::Cell::new(None),
         }
     }
 }
@@ -98,7 +134,10 @@ impl BaselineBot {
         BaselineBot {
             depth: level.depth(),
             enhanced: level.enhanced(),
+            repetition_aware: false,
             ctx: std::cell::RefCell::new(SearchContext::new()),
+            last_nodes: std::cell::Cell::new(0),
+            last_score: std::cell::Cell::new(None),
         }
     }
 
@@ -106,34 +145,117 @@ impl BaselineBot {
     pub fn reset(&self) {
         *self.ctx.borrow_mut() = SearchContext::new();
     }
-}
 
-impl Bot for BaselineBot {
-    fn choose_move(&self, game: &GameState) -> Option<Move> {
-        let legal = game.legal_moves();
-        if legal.is_empty() {
-            return None;
+    /// Nodes visited by the most recent `choose_move` call.
+    pub fn last_nodes(&self) -> u64 {
+        self.last_nodes.get()
+    }
+
+    /// Score of the move chosen by the most recent `choose_move` call, in
+    /// centipawns from the mover's perspective (`None` if no search ran).
+    pub fn last_score(&self) -> Option<i32> {
+        self.last_score.get()
+    }
+
+    /// Search every legal move and return them best first with their scores
+    /// (centipawns, mover's perspective); ties keep move generation order.
+    /// Records `last_nodes` and `last_score` like `choose_move`.
+    pub fn score_moves(&self, game: &GameState) -> Vec<(Move, i32)> {
+        self.score_moves_at_depth(game, self.depth)
+    }
+
+    /// Deepen one ply at a time up to `max_depth`, starting the next ply only if
+    /// its estimated cost still fits in `budget`. The budget is soft: depth 1
+    /// always completes, and an iteration that was started runs to the end even
+    /// if it overshoots. Returns the best move of the deepest finished iteration
+    /// with that depth (0 when there is no legal move). `last_nodes` covers all
+    /// iterations.
+    pub fn search_within(
+        &self,
+        game: &GameState,
+        max_depth: u32,
+        budget: Duration,
+    ) -> (Option<Move>, u32) {
+        let timer = Instant::now();
+        let mut best = None;
+        let mut reached = 0;
+        let mut nodes = 0;
+        let mut prev_nodes = None;
+        for depth in 1..=max_depth {
+            let started = timer.elapsed();
+            let scored = self.score_moves_at_depth(game, depth);
+            let iteration = timer.elapsed() - started;
+            nodes += self.last_nodes();
+            let Some(&(mv, _)) = scored.first() else {
+                break;
+            };
+            best = Some(mv);
+            reached = depth;
+            let growth = prev_nodes.map(|prev| self.last_nodes() as f64 / prev as f64);
+            if !next_iteration_fits(timer.elapsed(), iteration, growth, budget) {
+                break;
+            }
+            prev_nodes = Some(self.last_nodes().max(1));
         }
+        self.last_nodes.set(nodes);
+        (best, reached)
+    }
 
-        let scored = if self.enhanced {
+    fn score_moves_at_depth(&self, game: &GameState, depth: u32) -> Vec<(Move, i32)> {
+        self.last_nodes.set(0);
+        self.last_score.set(None);
+        if game.legal_moves().is_empty() {
+            return Vec::new();
+        }
+
+        let nodes_before = nodes_searched();
+        let history = game.position_history();
+        let mut scored = if self.enhanced {
             let mut ctx = self.ctx.borrow_mut();
-            best_move_with_scores_enhanced(&mut ctx, &game.board, self.depth)
+            if self.repetition_aware {
+                best_move_with_scores_enhanced_in_game(&mut ctx, &game.board, depth, history)
+            } else {
+                best_move_with_scores_enhanced(&mut ctx, &game.board, depth)
+            }
+        } else if self.repetition_aware {
+            best_move_with_scores_classic_in_game(&game.board, depth, history)
         } else {
-            best_move_with_scores_classic(&game.board, self.depth)
+            best_move_with_scores_classic(&game.board, depth)
         };
+        self.last_nodes.set(nodes_searched() - nodes_before);
 
-        if scored.is_empty() {
-            return legal.into_iter().next();
-        }
-
-        let best_score = scored.iter().map(|(_, s)| *s).max().unwrap();
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        self.last_score.set(scored.first().map(|&(_, score)| score));
         scored
-            .into_iter()
-            .find(|(_, s)| *s == best_score)
-            .map(|(mv, _)| mv)
     }
 }
 
+impl Bot for BaselineBot {
+    fn choose_move(&self, game: &GameState) -> Option<Move> {
+        self.score_moves(game).first().map(|&(mv, _)| mv)
+    }
+
+    /// Deepen up to `depth` within the move's budget; see `search_within`.
+    fn choose_move_timed(&self, game: &GameState, time: &TimeLeft) -> Option<Move> {
+        self.search_within(game, self.depth, time.move_budget()).0
+    }
+}
+/// Smallest growth assumed from one iteration to the next.
+const MIN_GROWTH: f64 = 4.0;
+
+/// Whether another iteration fits in `budget` after `elapsed`: it is expected to
+/// take as long as the last `iteration`, times the last iteration's node `growth`
+/// over the one before (at least `MIN_GROWTH`).
+fn next_iteration_fits(
+    elapsed: Duration,
+    iteration: Duration,
+    growth: Option<f64>,
+    budget: Duration,
+) -> bool {
+    let growth = growth.unwrap_or(MIN_GROWTH).max(MIN_GROWTH);
+    elapsed + iteration.mul_f64(growth) <= budget
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +307,131 @@ mod tests {
             "depth-1 bot should find Qh4# checkmate"
         );
     }
+
+    #[test]
+    fn move_budget_shares_the_clock() {
+        let time = TimeLeft {
+            remaining: Duration::from_secs(60),
+            increment: Duration::from_secs(2),
+        };
+        assert_eq!(time.move_budget(), Duration::from_millis(3500));
+        let low = TimeLeft {
+            remaining: Duration::from_secs(1),
+            increment: Duration::from_secs(10),
+        };
+        assert_eq!(low.move_budget(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn timed_search_matches_full_depth_with_time_to_spare() {
+        let game = GameState::new();
+        let bot = BaselineBot::from_level(Level::new(2).unwrap());
+        let plenty = TimeLeft {
+            remaining: Duration::from_secs(3600),
+            increment: Duration::ZERO,
+        };
+        assert_eq!(bot.choose_move_timed(&game, &plenty), bot.choose_move(&game));
+
+        // Out of time, the bot still plays a legal depth-1 move.
+        let none = TimeLeft {
+            remaining: Duration::ZERO,
+            increment: Duration::ZERO,
+        };
+        let mv = bot.choose_move_timed(&game, &none).unwrap();
+        assert!(game.legal_moves().contains(&mv));
+
+        assert_eq!(bot.search_within(&game, 3, Duration::ZERO).1, 1);
+        assert_eq!(bot.search_within(&game, 3, Duration::from_secs(3600)).1, 3);
+    }
+
+    #[test]
+    fn next_iteration_must_fit_the_budget() {
+        let ms = Duration::from_millis;
+        // 40ms so far, the last ply took 30ms: at 4x the next one needs 120ms.
+        assert!(next_iteration_fits(ms(40), ms(30), None, ms(160)));
+        assert!(!next_iteration_fits(ms(40), ms(30), None, ms(159)));
+        // A tree that grew 10x last time is expected to grow 10x again.
+        assert!(!next_iteration_fits(ms(40), ms(30), Some(10.0), ms(300)));
+        assert!(next_iteration_fits(ms(40), ms(30), Some(10.0), ms(340)));
+        // Slow growth still counts as 4x.
+        assert!(!next_iteration_fits(ms(40), ms(30), Some(1.5), ms(100)));
+        assert!(!next_iteration_fits(ms(0), ms(1), None, Duration::ZERO));
+    }
+
+    #[test]
+    fn choose_move_records_nodes_and_score() {
+        let bot = BaselineBot::from_level(Level::new(2).unwrap());
+        assert_eq!(bot.last_nodes(), 0);
+        assert_eq!(bot.last_score(), None);
+
+        let game = GameState::new();
+        bot.choose_move(&game).unwrap();
+        assert!(bot.last_nodes() > 20, "depth 2 should visit more than the root moves");
+        let score = bot.last_score().expect("search should record a score");
+        assert!(score.abs() < 200, "startpos score should be near zero, got {score}");
+    }
+
+    #[test]
+    fn score_moves_lists_every_move_best_first() {
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+        let game = GameState::from_fen(fen).unwrap();
+        let bot = BaselineBot::from_level(Level::new(2).unwrap());
+        let scored = bot.score_moves(&game);
+        assert_eq!(scored.len(), game.legal_moves().len());
+        assert!(scored.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(crate::uci::format_move(scored[0].0), "d8h4");
+        assert_eq!(bot.last_score(), Some(scored[0].1));
+        assert_eq!(bot.choose_move(&game), Some(scored[0].0));
+    }
+
+    #[test]
+    fn repetition_aware_scores_repeats_as_draws() {
+        // White is a rook down; Kg1 repeats a position already seen, which only
+        // the aware search scores as a draw.
+        let mut game = GameState::from_fen("6k1/r7/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        for uci in ["g1f1", "g8h8", "f1g1", "h8g8", "g1f1", "g8h8"] {
+            let mv = crate::uci::parse_legal_uci_move(&game.board, uci).unwrap();
+            assert!(game.make_move(mv));
+        }
+        for enhanced in [false, true] {
+            let mut bot = BaselineBot::from_level(Level::new(2).unwrap());
+            bot.enhanced = enhanced;
+            bot.choose_move(&game).unwrap();
+            let score = bot.last_score().unwrap();
+            assert!(score < -100, "unaware search should see the lost rook, got {score}");
+
+            bot.repetition_aware = true;
+            bot.reset();
+            let mv = bot.choose_move(&game).unwrap();
+            assert_eq!(crate::uci::format_move(mv), "f1g1");
+            assert_eq!(bot.last_score(), Some(0));
+        }
+    }
+
+    #[test]
+    fn repetition_draws_stay_out_of_the_shared_tt() {
+        // The knight and rook went out and back, so the aware search finds
+        // repetition draws that must not leak into later searches.
+        let moves = ["d5c3", "a8a2", "c3d5", "a2a8"];
+        let game = GameState::from_fen_and_moves("r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1", &moves)
+            .unwrap();
+        let by_move = |mut scored: Vec<(Move, i32)>| {
+            scored.sort_by_key(|&(mv, _)| crate::uci::format_move(mv));
+            scored
+        };
+        let mut bot = BaselineBot {
+            depth: 3,
+            repetition_aware: true,
+            ..BaselineBot::default()
+        };
+        bot.score_moves(&game);
+        bot.repetition_aware = false;
+        let reused = by_move(bot.score_moves(&game));
+
+        let fresh = BaselineBot {
+            depth: 3,
+            ..BaselineBot::default()
+        };
+        assert_eq!(reused, by_move(fresh.score_moves(&game)));
+    }
 }
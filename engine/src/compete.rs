@@ -0,0 +1,291 @@
+//! Game running and scoring shared by the `compete` binary and the GUI.
+//!
+//! A level plays every opening twice against a fresh baseline, once with the NN
+//! as White and once as Black. A win scores 1, a draw 0.5 and a loss 0.
+
+use crate::bot::Bot;
+use crate::game::{DrawReason, GameState, Outcome, STARTPOS};
+use crate::openings::OpeningEntry;
+use crate::{format_move, format_san, BaselineBot, Color, NnEvalBot};
+use std::time::Instant;
+
+/// Games still running after this many plies are adjudicated a draw.
+pub const MAX_PLIES: usize = 500;
+/// Share of the available points needed to pass a level.
+pub const PASS_THRESHOLD: f64 = 0.70;
+
+/// Search effort reported by whichever engine made the move.
+#[derive(Clone, Copy, Debug)]
+pub enum EngineStats {
+    Nn {
+        inference_calls: u64,
+        positions: u64,
+    },
+    Baseline {
+        nodes: u64,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveRecord {
+    pub uci: String,
+    pub san: String,
+    /// Position after the move.
+    pub fen: String,
+    pub side: String, // "white" or "black"
+    pub time_ms: f64,
+    /// Score of the chosen move from the mover's perspective
+    /// (centipawns for the baseline, raw network output for the NN).
+    pub score: Option<f64>,
+    pub stats: EngineStats,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    pub plies: usize,
+    /// The NN's moves in UCI notation.
+    pub nn_moves: Vec<String>,
+    pub starting_fen: String,
+    pub move_history: Vec<MoveRecord>,
+    /// "checkmate", a draw reason name, "max_plies", "forfeit" or "aborted".
+    pub reason: String,
+}
+
+/// Play one NN vs baseline game from `starting_fen` (the standard position when
+/// `None`). `on_move` sees the game after every move; returning false abandons
+/// it as an "aborted" draw.
+pub fn run_game(
+    nn: &NnEvalBot,
+    baseline: &BaselineBot,
+    starting_fen: Option<&str>,
+    nn_is_white: bool,
+    mut on_move: impl FnMut(&GameState, &[MoveRecord]) -> bool,
+) -> GameResult {
+    let actual_fen = starting_fen.unwrap_or(STARTPOS);
+
+    let mut game = match GameState::from_fen(actual_fen) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("Warning: bad FEN ({e}), falling back to startpos");
+            GameState::new()
+        }
+    };
+
+    let mut plies = 0;
+    let mut nn_moves: Vec<String> = Vec::new();
+    let mut move_history: Vec<MoveRecord> = Vec::new();
+    let finish = |outcome, plies, nn_moves, move_history, reason: &str| GameResult {
+        outcome,
+        plies,
+        nn_moves,
+        starting_fen: actual_fen.to_string(),
+        move_history,
+        reason: reason.to_string(),
+    };
+
+    loop {
+        if game.is_game_over() {
+            let outcome = game
+                .outcome()
+                .unwrap_or(Outcome::Draw(DrawReason::Adjudication));
+            let reason = match outcome {
+                Outcome::Checkmate { .. } => "checkmate",
+                Outcome::Draw(reason) => reason.name(),
+            };
+            return finish(outcome, plies, nn_moves, move_history, reason);
+        }
+
+        if plies >= MAX_PLIES {
+            let outcome = Outcome::Draw(DrawReason::Adjudication);
+            return finish(outcome, plies, nn_moves, move_history, "max_plies");
+        }
+
+        let side = game.side_to_move();
+        let is_nn_turn =
+            (side == Color::White && nn_is_white) || (side == Color::Black && !nn_is_white);
+
+        let (calls_before, positions_before) = nn.counters();
+        let timer = Instant::now();
+        let mv = if is_nn_turn {
+            nn.choose_move(&game)
+        } else {
+            baseline.choose_move(&game)
+        };
+        let time_ms = timer.elapsed().as_secs_f64() * 1000.0;
+
+        let Some(mv) = mv else {
+            let outcome = Outcome::Checkmate { winner: !side };
+            return finish(outcome, plies, nn_moves, move_history, "forfeit");
+        };
+
+        let (stats, score) = if is_nn_turn {
+            let (calls, positions) = nn.counters();
+            let stats = EngineStats::Nn {
+                inference_calls: calls - calls_before,
+                positions: positions - positions_before,
+            };
+            (stats, nn.last_score().map(f64::from))
+        } else {
+            let stats = EngineStats::Baseline {
+                nodes: baseline.last_nodes(),
+            };
+            (stats, baseline.last_score().map(f64::from))
+        };
+        let uci = format_move(mv);
+        let san = format_san(&game.board, mv);
+        let side_str = if side == Color::White {
+            "white"
+        } else {
+            "black"
+        };
+        if is_nn_turn {
+            nn_moves.push(uci.clone());
+        }
+        game.make_move(mv);
+        let fen = game.board.to_string();
+        move_history.push(MoveRecord {
+            uci,
+            san,
+            fen,
+            side: side_str.to_string(),
+            time_ms,
+            score,
+            stats,
+        });
+        plies += 1;
+        if !on_move(&game, &move_history) {
+            let outcome = Outcome::Draw(DrawReason::Adjudication);
+            return finish(outcome, plies, nn_moves, move_history, "aborted");
+        }
+    }
+}
+
+/// All openings in file order (deterministic, no sampling), or just the standard
+/// starting position when there are none.
+pub fn select_positions(openings: &[OpeningEntry]) -> Vec<OpeningEntry> {
+    if openings.is_empty() {
+        return vec![OpeningEntry {
+            fen: STARTPOS.to_string(),
+            name: Some("Starting position".to_string()),
+            ..OpeningEntry::default()
+        }];
+    }
+    openings.to_vec()
+}
+
+/// Points the NN earns from `outcome` playing `nn_color`.
+pub fn score_outcome(outcome: &Outcome, nn_color: Color) -> f64 {
+    match outcome {
+        Outcome::Checkmate { winner } => {
+            if *winner == nn_color {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        Outcome::Draw(_) => 0.5,
+    }
+}
+
+/// Points needed to pass a level of `total_games` games.
+pub fn pass_points(total_games: usize) -> usize {
+    (total_games as f64 * PASS_THRESHOLD).ceil() as usize
+}
+
+/// Running W/D/L record of a level.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LevelTally {
+    pub score: f64,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl LevelTally {
+    /// Add one game scored by `score_outcome`.
+    pub fn record(&mut self, points: f64) {
+        self.score += points;
+        match GameScore::from_points(points) {
+            GameScore::Win => self.wins += 1,
+            GameScore::Draw => self.draws += 1,
+            GameScore::Loss => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// "WIN ", "LOSS" or "DRAW" for one game's points.
+    pub fn label(points: f64) -> &'static str {
+        match GameScore::from_points(points) {
+            GameScore::Win => "WIN ",
+            GameScore::Draw => "DRAW",
+            GameScore::Loss => "LOSS",
+        }
+    }
+}
+
+/// A game's result from the NN's point of view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameScore {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl GameScore {
+    pub fn from_points(points: f64) -> Self {
+        if points >= 1.0 {
+            GameScore::Win
+        } else if points > 0.0 {
+            GameScore::Draw
+        } else {
+            GameScore::Loss
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_from_the_nn_side() {
+        let white_mates = Outcome::Checkmate {
+            winner: Color::White,
+        };
+        assert_eq!(score_outcome(&white_mates, Color::White), 1.0);
+        assert_eq!(score_outcome(&white_mates, Color::Black), 0.0);
+        let draw = Outcome::Draw(DrawReason::Stalemate);
+        assert_eq!(score_outcome(&draw, Color::Black), 0.5);
+    }
+
+    #[test]
+    fn pass_points_round_up() {
+        assert_eq!(pass_points(50), 35);
+        assert_eq!(pass_points(2), 2);
+        assert_eq!(pass_points(11), 8);
+    }
+
+    #[test]
+    fn tally_counts_results() {
+        let mut tally = LevelTally::default();
+        for points in [1.0, 0.5, 0.0, 1.0] {
+            tally.record(points);
+        }
+        assert_eq!((tally.wins, tally.draws, tally.losses), (2, 1, 1));
+        assert_eq!(tally.score, 2.5);
+        assert_eq!(tally.games(), 4);
+        assert_eq!(LevelTally::label(0.5), "DRAW");
+        assert_eq!(GameScore::from_points(0.0), GameScore::Loss);
+    }
+
+    #[test]
+    fn falls_back_to_the_starting_position() {
+        let positions = select_positions(&[]);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].fen, STARTPOS);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::san::is_castle;
+use crate::uci::piece_unicode;
+use crate::{Board, Color, File, Move, Rank, Square};
+
+/// Render the board as text with `bottom`'s pieces at the bottom, one rank per line,
+/// with file letters above and below. Squares in `highlight` are drawn in ANSI reverse video.
+pub fn board_diagram(board: &Board, bottom: Color, highlight: &[Square]) -> String {
+    let files: Vec<usize> = if bottom == Color::White {
+        (0..8).collect()
+    } else {
+        (0..8).rev().collect()
+    };
+    let ranks: Vec<usize> = if bottom == Color::White {
+        (0..8).rev().collect()
+    } else {
+        (0..8).collect()
+    };
+    let header: Vec<String> = files
+        .iter()
+        .map(|&f| ((b'a' + f as u8) as char).to_string())
+        .collect();
+    let header = format!("  {}\n", header.join(" "));
+
+    let mut out = header.clone();
+    for &rank in &ranks {
+        out.push_str(&format!("{} ", rank + 1));
+        for &file in &files {
+            let sq = Square::new(File::index(file), Rank::index(rank));
+            let glyph = match board.piece_on(sq) {
+                Some(piece) => {
+                    let color = if board.colors(Color::White).has(sq) {
+                        Color::White
+                    } else {
+                        Color::Black
+                    };
+                    piece_unicode(piece, color)
+                }
+                None => ".",
+            };
+            if highlight.contains(&sq) {
+                out.push_str(&format!("\x1b[7m{glyph}\x1b[0m "));
+            } else {
+                out.push_str(&format!("{glyph} "));
+            }
+        }
+        out.push_str(&format!("{}\n", rank + 1));
+    }
+    out.push_str(&header);
+    out
+}
+
+/// The squares to highlight for `mv` played from `board`. Castling, which cozy-chess
+/// encodes as the king taking its own rook, is shown on the king's destination.
+pub fn move_squares(board: &Board, mv: Move) -> [Square; 2] {
+    if is_castle(board, mv) {
+        let file = if mv.to.file() > mv.from.file() {
+            File::G
+        } else {
+            File::C
+        };
+        [mv.from, Square::new(file, mv.from.rank())]
+    } else {
+        [mv.from, mv.to]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci::parse_uci_move;
+
+    #[test]
+    fn board_diagram_flips_and_highlights() {
+        let board = Board::default();
+        let white = board_diagram(&board, Color::White, &[]);
+        let lines: Vec<&str> = white.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "  a b c d e f g h");
+        assert_eq!(lines[1], "8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜ 8");
+        assert_eq!(lines[8], "1 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖ 1");
+
+        let black = board_diagram(&board, Color::Black, &[]);
+        let lines: Vec<&str> = black.lines().collect();
+        assert_eq!(lines[0], "  h g f e d c b a");
+        assert_eq!(lines[1], "1 ♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖ 1");
+
+        let e4 = Square::new(File::E, Rank::Fourth);
+        let marked = board_diagram(&board, Color::White, &[e4]);
+        assert_eq!(
+            marked.lines().nth(5),
+            Some("4 . . . . \x1b[7m.\x1b[0m . . . 4")
+        );
+    }
+
+    #[test]
+    fn castling_highlights_the_kings_destination() {
+        let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+        let sq = |s: &str| s.parse::<Square>().unwrap();
+        let short = parse_uci_move("e1h1").unwrap();
+        assert_eq!(move_squares(&board, short), [sq("e1"), sq("g1")]);
+        let long = parse_uci_move("e1a1").unwrap();
+        assert_eq!(move_squares(&board, long), [sq("e1"), sq("c1")]);
+        let rook = parse_uci_move("h1h8").unwrap();
+        assert_eq!(move_squares(&board, rook), [sq("h1"), sq("h8")]);
+    }
+}
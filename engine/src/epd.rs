@@ -0,0 +1,204 @@
+use cozy_chess::{Board, Move};
+
+use crate::san::parse_san;
+use crate::uci::parse_legal_uci_move;
+
+/// A parsed EPD record: the four position fields of a FEN plus opcodes.
+///
+/// Example: `rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - id "King's Pawn"; hmvc 0;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Epd {
+    /// Piece placement, side to move, castling and en passant fields.
+    pub position: String,
+    /// Opcodes in file order, each with its (unquoted) operands.
+    pub ops: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    /// Operands of the first opcode named `name`.
+    pub fn op(&self, name: &str) -> Option<&[String]> {
+        self.ops
+            .iter()
+            .find(|(op, _)| op == name)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// The `id` opcode, conventionally the position's name.
+    pub fn id(&self) -> Option<&str> {
+        self.op("id")
+            .and_then(|operands| operands.first())
+            .map(|s| s.as_str())
+    }
+
+    /// Resolve the operands of `name` (e.g. `bm`, `am`) to legal moves in `board`.
+    /// Operands may be SAN or UCI; an unresolvable operand is an error.
+    pub fn moves(&self, name: &str, board: &Board) -> Result<Vec<Move>, String> {
+        self.op(name)
+            .unwrap_or(&[])
+            .iter()
+            .map(|text| {
+                parse_san(board, text)
+                    .or_else(|| parse_legal_uci_move(board, text))
+                    .ok_or_else(|| format!("{name} move '{text}' is not legal here"))
+            })
+            .collect()
+    }
+
+    /// Full six-field FEN, taking the clocks from `hmvc`/`fmvn` when present.
+    pub fn to_fen(&self) -> String {
+        let clock = |name: &str, default: &str| {
+            self.op(name)
+                .and_then(|operands| operands.first())
+                .filter(|v| v.parse::<u32>().is_ok())
+                .cloned()
+                .unwrap_or_else(|| default.to_string())
+        };
+        format!(
+            "{} {} {}",
+            self.position,
+            clock("hmvc", "0"),
+            clock("fmvn", "1")
+        )
+    }
+}
+
+/// Split an opcode section into tokens, keeping quoted strings together and
+/// treating `;` as its own token.
+fn tokenize(s: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            chars.next();
+            tokens.push((";".to_string(), false));
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => {
+                        if let Some(escaped) = chars.next() {
+                            text.push(escaped);
+                        }
+                    }
+                    Some(ch) => text.push(ch),
+                    None => return Err("unterminated string operand".to_string()),
+                }
+            }
+            tokens.push((text, true));
+        } else {
+            let mut text = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() || ch == ';' {
+                    break;
+                }
+                text.push(ch);
+                chars.next();
+            }
+            tokens.push((text, false));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a single EPD line. Opcodes are optional; a trailing `;` may be omitted.
+pub fn parse_epd(line: &str) -> Result<Epd, String> {
+    let line = line.trim();
+    let mut rest = line;
+    let mut fields = Vec::with_capacity(4);
+    for _ in 0..4 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return Err(format!("EPD needs 4 position fields: {line}"));
+        }
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let mut ops = Vec::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    for (token, quoted) in tokenize(rest)? {
+        if token == ";" && !quoted {
+            if let Some(op) = current.take() {
+                ops.push(op);
+            }
+        } else if let Some((_, operands)) = current.as_mut() {
+            operands.push(token);
+        } else if quoted {
+            return Err(format!("expected opcode, found string \"{token}\""));
+        } else {
+            current = Some((token, Vec::new()));
+        }
+    }
+    if let Some(op) = current.take() {
+        ops.push(op);
+    }
+
+    Ok(Epd {
+        position: fields.join(" "),
+        ops,
+    })
+}
+
+/// Quote an EPD string operand.
+pub fn quote_operand(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_id_and_clocks() {
+        let epd = parse_epd(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - id \"C50 Italian Game\"; hmvc 3; fmvn 3;",
+        )
+        .unwrap();
+        assert_eq!(epd.id(), Some("C50 Italian Game"));
+        assert_eq!(
+            epd.to_fen(),
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3"
+        );
+    }
+
+    #[test]
+    fn multiple_operands_and_missing_semicolon() {
+        let epd = parse_epd("6k1/8/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; am Rd7 Rd6").unwrap();
+        assert_eq!(epd.op("bm").unwrap(), ["Rd8#"]);
+        assert_eq!(epd.op("am").unwrap(), ["Rd7", "Rd6"]);
+        assert_eq!(epd.id(), None);
+        assert_eq!(epd.to_fen(), "6k1/8/8/8/8/8/5PPP/3R2K1 w - - 0 1");
+    }
+
+    #[test]
+    fn resolves_bm_and_am_moves() {
+        let epd = parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; am Kf1 h2h3;").unwrap();
+        let board: Board = epd.to_fen().parse().unwrap();
+        let bm = epd.moves("bm", &board).unwrap();
+        assert_eq!(bm.len(), 1);
+        assert_eq!(bm[0].to_string(), "a1a8");
+        assert_eq!(epd.moves("am", &board).unwrap().len(), 2);
+        assert!(epd.moves("pv", &board).unwrap().is_empty());
+
+        let bad = parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Qd8;").unwrap();
+        assert!(bad.moves("bm", &board).is_err());
+    }
+
+    #[test]
+    fn rejects_short_lines() {
+        assert!(parse_epd("8/8/8/8 w").is_err());
+        assert!(parse_epd("8/8/8/8/8/8/8/8 w - - id \"open").is_err());
+    }
+
+    #[test]
+    fn quoted_operand_round_trip() {
+        let quoted = quote_operand("Queen's \"Gambit\"");
+        let epd = parse_epd(&format!("8/8/8/8/8/8/8/8 w - - id {quoted};")).unwrap();
+        assert_eq!(epd.id(), Some("Queen's \"Gambit\""));
+    }
+}
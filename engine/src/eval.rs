@@ -10,7 +10,7 @@ pub const BISHOP_VALUE: i32 = 330;
 pub const ROOK_VALUE: i32 = 500;
 pub const QUEEN_VALUE: i32 = 900;
 
-fn piece_value(piece: Piece) -> i32 {
+pub fn piece_value(piece: Piece) -> i32 {
     match piece {
         Piece::Pawn => PAWN_VALUE,
         Piece::Knight => KNIGHT_VALUE,
@@ -33,7 +33,7 @@ const QUEEN_PHASE: i32 = 4;
 const TOTAL_PHASE: i32 = 2 * (2 * KNIGHT_PHASE + 2 * BISHOP_PHASE + 2 * ROOK_PHASE + QUEEN_PHASE); // 24
 
 /// Returns game phase: 256 = pure middlegame, 0 = pure endgame.
-fn game_phase(board: &Board) -> i32 {
+pub fn game_phase(board: &Board) -> i32 {
     let mut phase = 0;
     for color in [Color::White, Color::Black] {
         phase += board.colored_pieces(color, Piece::Knight).len() as i32 * KNIGHT_PHASE;
@@ -308,7 +308,7 @@ fn piece_eval(board: &Board, color: Color) -> (i32, i32, i32) {
     let enemy_pawn_attacks = {
         let mut a = cozy_chess::BitBoard::EMPTY;
         for sq in board.colored_pieces(them, Piece::Pawn) {
-            a = a | get_pawn_attacks(sq, them);
+            a |= get_pawn_attacks(sq, them);
         }
         a
     };
@@ -339,7 +339,7 @@ fn piece_eval(board: &Board, color: Color) -> (i32, i32, i32) {
     for sq in board.colored_pieces(color, Piece::Queen) {
         let moves = get_rook_moves(sq, occupied) | get_bishop_moves(sq, occupied);
         let count = moves.len() as i32;
-        mob_mg += (count - 14) * 1;
+        mob_mg += count - 14;
         mob_eg += (count - 14) * 2;
     }
 
@@ -399,7 +399,7 @@ static FORWARD_MASKS: std::sync::LazyLock<ForwardMasks> = std::sync::LazyLock::n
             let mut w = cozy_chess::BitBoard::EMPTY;
             for f in lo..=hi {
                 for r in (rank + 1)..8 {
-                    w = w | Square::new(File::index(f), Rank::index(r)).bitboard();
+                    w |= Square::new(File::index(f), Rank::index(r)).bitboard();
                 }
             }
             masks.white[rank * 8 + file] = w;
@@ -408,7 +408,7 @@ static FORWARD_MASKS: std::sync::LazyLock<ForwardMasks> = std::sync::LazyLock::n
             let mut b = cozy_chess::BitBoard::EMPTY;
             for f in lo..=hi {
                 for r in 0..rank {
-                    b = b | Square::new(File::index(f), Rank::index(r)).bitboard();
+                    b |= Square::new(File::index(f), Rank::index(r)).bitboard();
                 }
             }
             masks.black[rank * 8 + file] = b;
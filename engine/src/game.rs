@@ -1,44 +1,141 @@
-use cozy_chess::{Board, Color, GameStatus, Move};
+use cozy_chess::{BitBoard, Board, Color, Move, Piece};
 use std::collections::HashMap;
 
+use crate::pgn::{parse_pgn, quote_tag_value, wrap_movetext, PgnGame};
+use crate::san::{format_san, parse_san};
+use crate::uci::parse_legal_uci_move;
+
+/// FEN of the standard starting position.
+pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// With the `serde` feature, a game serializes as its starting FEN and UCI
+/// moves, and deserializing replays them (so repetition counts are rebuilt).
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "GameRecord", try_from = "GameRecord")
+)]
 pub struct GameState {
     pub board: Board,
     pub history: Vec<Move>,
     position_counts: HashMap<u64, u32>,
+    /// Hash of every position reached, starting with the initial one.
+    hashes: Vec<u64>,
+    /// Position before each move in `history`.
+    boards: Vec<Board>,
 }
 
 impl GameState {
     pub fn new() -> Self {
-        let board = Board::default();
-        let mut position_counts = HashMap::new();
-        position_counts.insert(board.hash(), 1);
-        GameState {
-            board,
-            history: Vec::new(),
-            position_counts,
-        }
+        Self::from_board(Board::default())
     }
 
     pub fn from_board(board: Board) -> Self {
         let mut position_counts = HashMap::new();
-        position_counts.insert(board.hash(), 1);
+        let board_hash = board.hash();
+        position_counts.insert(board_hash, 1);
         GameState {
             board,
             history: Vec::new(),
             position_counts,
+            hashes: vec![board_hash],
+            boards: Vec::new(),
         }
     }
 
     pub fn from_fen(fen: &str) -> Result<Self, String> {
         let board: Board = fen.parse().map_err(|e| format!("Invalid FEN: {:?}", e))?;
-        let mut position_counts = HashMap::new();
-        position_counts.insert(board.hash(), 1);
-        Ok(GameState {
-            board,
-            history: Vec::new(),
-            position_counts,
-        })
+        Ok(Self::from_board(board))
+    }
+
+    /// Start from `fen` and play `moves` (UCI), e.g. to rebuild a game from
+    /// `compete` JSON or a `play-move` request.
+    pub fn from_fen_and_moves<S: AsRef<str>>(fen: &str, moves: &[S]) -> Result<Self, String> {
+        let mut game = Self::from_fen(fen)?;
+        for (ply, uci) in moves.iter().enumerate() {
+            let uci = uci.as_ref();
+            let mv = parse_legal_uci_move(&game.board, uci)
+                .ok_or_else(|| format!("Illegal move {uci} at ply {}", ply + 1))?;
+            game.make_move(mv);
+        }
+        Ok(game)
+    }
+
+    /// Load the first game of a PGN document (see `from_pgn_game`).
+    pub fn from_pgn(text: &str) -> Result<Self, String> {
+        let pgn = parse_pgn(text)
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No game found in PGN".to_string())?;
+        Self::from_pgn_game(&pgn)
+    }
+
+    /// Replay a parsed PGN game's mainline from its `FEN` tag (or the standard
+    /// start position).
+    pub fn from_pgn_game(pgn: &PgnGame) -> Result<Self, String> {
+        let mut game = match pgn.tag("FEN") {
+            Some(fen) => Self::from_fen(fen)?,
+            None => Self::new(),
+        };
+        for (ply, san) in pgn.moves.iter().enumerate() {
+            let mv = parse_san(&game.board, san)
+                .ok_or_else(|| format!("Illegal move {san} at ply {}", ply + 1))?;
+            game.make_move(mv);
+        }
+        Ok(game)
+    }
+
+    /// The game as PGN. The Seven Tag Roster is filled with "?" placeholders
+    /// unless given in `tags`, `Result` defaults to `outcome()` ("*" while the
+    /// game is in progress), and `SetUp`/`FEN` are added for non-standard starts.
+    pub fn to_pgn(&self, tags: &[(&str, &str)]) -> String {
+        const ROSTER: [&str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+        let result = match self.outcome() {
+            Some(Outcome::Checkmate {
+                winner: Color::White,
+            }) => "1-0",
+            Some(Outcome::Checkmate {
+                winner: Color::Black,
+            }) => "0-1",
+            Some(Outcome::Draw(_)) => "1/2-1/2",
+            None => "*",
+        };
+        let given = |name: &str| tags.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+        let result = given("Result").unwrap_or(result);
+        let start = self.board_at_ply(0).expect("initial position");
+
+        let mut pgn = String::new();
+        let mut push_tag = |name: &str, value: &str| {
+            pgn.push_str(&format!("[{} {}]\n", name, quote_tag_value(value)));
+        };
+        for name in ROSTER {
+            push_tag(name, given(name).unwrap_or("?"));
+        }
+        push_tag("Result", result);
+        if *start != Board::default() {
+            push_tag("SetUp", "1");
+            push_tag("FEN", &start.to_string());
+        }
+        for &(name, value) in tags {
+            if !ROSTER.contains(&name) && !matches!(name, "Result" | "SetUp" | "FEN") {
+                push_tag(name, value);
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        for (ply, mv, board) in self.plies() {
+            if board.side_to_move() == Color::White {
+                tokens.push(format!("{}.", board.fullmove_number()));
+            } else if ply == 0 {
+                tokens.push(format!("{}...", board.fullmove_number()));
+            }
+            tokens.push(format_san(board, mv));
+        }
+        tokens.push(result.to_string());
+        pgn.push_str(&wrap_movetext(&tokens));
+        pgn
     }
 
     pub fn legal_moves(&self) -> Vec<Move> {
@@ -65,36 +162,114 @@ impl GameState {
             return false;
         }
 
+        self.boards.push(self.board.clone());
         self.board.play(mv);
         self.history.push(mv);
         let hash = self.board.hash();
         *self.position_counts.entry(hash).or_insert(0) += 1;
+        self.hashes.push(hash);
         true
     }
 
+    /// Take back the last move, returning it (`None` at the start of the game).
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let mv = self.history.pop()?;
+        let hash = self.hashes.pop().expect("one hash per position");
+        if let Some(count) = self.position_counts.get_mut(&hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.position_counts.remove(&hash);
+            }
+        }
+        self.board = self.boards.pop().expect("one board per move");
+        Some(mv)
+    }
+
+    /// Position after the first `ply` moves (0 = the initial position), or
+    /// `None` past the end of the game.
+    pub fn board_at_ply(&self, ply: usize) -> Option<&Board> {
+        if ply == self.history.len() {
+            Some(&self.board)
+        } else {
+            self.boards.get(ply)
+        }
+    }
+
+    /// FEN of `board_at_ply(ply)`.
+    pub fn fen_at_ply(&self, ply: usize) -> Option<String> {
+        self.board_at_ply(ply).map(|b| b.to_string())
+    }
+
+    /// Each move with its 0-based ply and the position it was played from.
+    pub fn plies(&self) -> impl Iterator<Item = (usize, Move, &Board)> {
+        self.history
+            .iter()
+            .zip(&self.boards)
+            .enumerate()
+            .map(|(ply, (&mv, board))| (ply, mv, board))
+    }
+
+    /// Hashes of the positions reached in this game, oldest first, ending with
+    /// the current position. Lets searches score repetitions as draws.
+    pub fn position_history(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    /// True if `next`, reached by a move from the current position, repeats a
+    /// position of this game since the last capture or pawn move.
+    pub fn is_repetition_after(&self, next: &Board) -> bool {
+        let hash = next.hash();
+        self.hashes
+            .iter()
+            .rev()
+            .take(next.halfmove_clock() as usize)
+            .any(|&h| h == hash)
+    }
+
     pub fn is_game_over(&self) -> bool {
-        self.board.status() != GameStatus::Ongoing || self.is_threefold_repetition()
+        self.outcome().is_some()
     }
 
-    pub fn is_threefold_repetition(&self) -> bool {
+    /// Times the current position has occurred in this game, including now.
+    pub fn repetitions(&self) -> u32 {
         self.position_counts
             .get(&self.board.hash())
             .copied()
             .unwrap_or(0)
-            >= 3
     }
 
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetitions() >= 3
+    }
+
+    /// Game result, if the game is over. Checkmate and stalemate take precedence
+    /// over the other draws; the fifty-move rule and threefold repetition end the
+    /// game automatically rather than on a claim.
     pub fn outcome(&self) -> Option<Outcome> {
+        let mut has_moves = false;
+        self.board.generate_moves(|_| {
+            has_moves = true;
+            true
+        });
+        if !has_moves {
+            return Some(if self.board.checkers().is_empty() {
+                Outcome::Draw(DrawReason::Stalemate)
+            } else {
+                Outcome::Checkmate {
+                    winner: !self.board.side_to_move(),
+                }
+            });
+        }
+        if is_insufficient_material(&self.board) {
+            return Some(Outcome::Draw(DrawReason::InsufficientMaterial));
+        }
         if self.is_threefold_repetition() {
-            return Some(Outcome::Draw);
+            return Some(Outcome::Draw(DrawReason::Repetition));
         }
-        match self.board.status() {
-            GameStatus::Won => Some(Outcome::Checkmate {
-                winner: !self.board.side_to_move(),
-            }),
-            GameStatus::Drawn => Some(Outcome::Draw),
-            GameStatus::Ongoing => None,
+        if self.board.halfmove_clock() >= 100 {
+            return Some(Outcome::Draw(DrawReason::FiftyMove));
         }
+        None
     }
 
     pub fn side_to_move(&self) -> Color {
@@ -102,16 +277,101 @@ impl GameState {
     }
 }
 
+/// Serialized form of a `GameState`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GameRecord {
+    fen: String,
+    moves: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<GameState> for GameRecord {
+    fn from(game: GameState) -> Self {
+        GameRecord {
+            fen: game.fen_at_ply(0).expect("initial position"),
+            moves: game
+                .history
+                .iter()
+                .map(|&mv| crate::uci::format_move(mv))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GameRecord> for GameState {
+    type Error = String;
+
+    fn try_from(record: GameRecord) -> Result<Self, String> {
+        GameState::from_fen_and_moves(&record.fen, &record.moves)
+    }
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// True when neither side can possibly checkmate: K v K, K+minor v K, or kings
+/// with bishops only, all on squares of the same colour.
+pub fn is_insufficient_material(board: &Board) -> bool {
+    let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if !heavy.is_empty() {
+        return false;
+    }
+    let knights = board.pieces(Piece::Knight);
+    let bishops = board.pieces(Piece::Bishop);
+    let minors = knights.len() + bishops.len();
+    if minors <= 1 {
+        return true;
+    }
+    if !knights.is_empty() {
+        return false;
+    }
+    (bishops & BitBoard::LIGHT_SQUARES).is_empty() || (bishops & BitBoard::DARK_SQUARES).is_empty()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    Repetition,
+    FiftyMove,
+    InsufficientMaterial,
+    /// Declared by a runner rather than the rules, e.g. `compete`'s ply limit.
+    /// Never returned by `GameState::outcome`.
+    Adjudication,
+}
+
+impl DrawReason {
+    /// Snake-case name used in JSON and reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::Repetition => "repetition",
+            DrawReason::FiftyMove => "fifty_move",
+            DrawReason::InsufficientMaterial => "insufficient_material",
+            DrawReason::Adjudication => "adjudication",
+        }
+    }
+
+    /// Human-readable description, e.g. for "Draw by ...".
+    pub fn description(self) -> &'static str {
+        match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::Repetition => "threefold repetition",
+            DrawReason::FiftyMove => "the fifty-move rule",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::Adjudication => "adjudication",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Outcome {
     Checkmate { winner: Color },
-    Draw,
+    Draw(DrawReason),
 }
 
 #[cfg(test)]
@@ -126,7 +386,163 @@ mod tests {
         assert_eq!(game.side_to_move(), Color::Black);
         assert!(game.history.is_empty());
         assert!(!game.is_game_over());
-        assert!(game.legal_moves().len() > 0);
+        assert!(!game.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn undo_restores_positions_and_repetition_counts() {
+        let mut game = GameState::new();
+        let start = game.board.clone();
+        let moves: Vec<Move> = [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        for &mv in &moves {
+            assert!(game.make_move(mv));
+        }
+        assert_eq!(game.outcome(), Some(Outcome::Draw(DrawReason::Repetition)));
+        assert_eq!(game.board_at_ply(0), Some(&start));
+        assert_eq!(game.board_at_ply(8), Some(&game.board));
+        assert_eq!(game.board_at_ply(9), None);
+        assert_eq!(
+            game.fen_at_ply(1).unwrap(),
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"
+        );
+        let replayed: Vec<Move> = game.plies().map(|(_, mv, _)| mv).collect();
+        assert_eq!(replayed, moves);
+        assert!(game
+            .plies()
+            .all(|(ply, _, b)| game.board_at_ply(ply) == Some(b)));
+
+        assert_eq!(game.undo_move(), Some(moves[7]));
+        assert_eq!(game.outcome(), None);
+        assert!(game.make_move(moves[7]));
+        assert!(game.is_threefold_repetition());
+
+        while game.undo_move().is_some() {}
+        assert_eq!(game.board, start);
+        assert!(game.history.is_empty());
+        assert_eq!(game.position_history(), &[start.hash()]);
+        assert_eq!(game.undo_move(), None);
+    }
+
+    #[test]
+    fn pgn_round_trip_rebuilds_repetitions() {
+        let fen = "6k1/r7/8/8/8/8/5PPP/6K1 b - - 0 30";
+        let game = GameState::from_fen_and_moves(
+            fen,
+            &[
+                "g8h8", "g1f1", "h8g8", "f1g1", "g8h8", "g1f1", "h8g8", "f1g1",
+            ],
+        )
+        .unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Draw(DrawReason::Repetition)));
+
+        let pgn = game.to_pgn(&[("White", "Alice"), ("Termination", "repetition")]);
+        assert!(pgn.contains("[White \"Alice\"]\n[Black \"?\"]\n[Result \"1/2-1/2\"]"));
+        assert!(pgn.contains(&format!("[FEN \"{fen}\"]\n[Termination \"repetition\"]")));
+        assert!(pgn.contains("30... Kh8 31. Kf1 Kg8 32. Kg1"));
+
+        let loaded = GameState::from_pgn(&pgn).unwrap();
+        assert_eq!(loaded.history, game.history);
+        assert_eq!(loaded.fen_at_ply(0).unwrap(), fen);
+        assert_eq!(loaded.position_history(), game.position_history());
+        assert!(loaded.is_threefold_repetition());
+
+        assert!(GameState::from_fen_and_moves(fen, &["g8h8", "g1g3"]).is_err());
+        assert!(GameState::from_pgn("1. e4 e5 2. Ke3").is_err());
+        assert!(GameState::new()
+            .to_pgn(&[])
+            .ends_with("[Result \"*\"]\n\n*\n"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_record_round_trip() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let game = GameState::from_fen_and_moves(fen, &["e2e4", "e7e5", "g1f3"]).unwrap();
+        let record = GameRecord::from(game.clone());
+        assert_eq!(record.fen, fen);
+        assert_eq!(record.moves, ["e2e4", "e7e5", "g1f3"]);
+        let back = GameState::try_from(record).unwrap();
+        assert_eq!(back.board, game.board);
+        assert_eq!(back.position_history(), game.position_history());
+        let bad = GameRecord {
+            fen: fen.to_string(),
+            moves: vec!["e2e5".to_string()],
+        };
+        assert!(GameState::try_from(bad).is_err());
+    }
+
+    fn outcome_of(fen: &str) -> Option<Outcome> {
+        GameState::from_fen(fen).unwrap().outcome()
+    }
+
+    #[test]
+    fn outcome_checkmate_and_stalemate() {
+        let mate = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+        assert_eq!(
+            outcome_of(mate),
+            Some(Outcome::Checkmate {
+                winner: Color::Black
+            })
+        );
+        let stalemate = "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1";
+        assert_eq!(
+            outcome_of(stalemate),
+            Some(Outcome::Draw(DrawReason::Stalemate))
+        );
+    }
+
+    #[test]
+    fn outcome_insufficient_material() {
+        let draws = [
+            "8/8/4k3/8/8/3K4/8/8 w - - 0 1",     // K v K
+            "8/8/4k3/8/8/3KN3/8/8 w - - 0 1",    // KN v K
+            "8/8/4kb2/8/8/3K4/8/8 w - - 0 1",    // K v KB
+            "8/8/2b1k3/8/8/3K4/4B3/8 w - - 0 1", // KB v KB, both on light squares
+        ];
+        for fen in draws {
+            assert_eq!(
+                outcome_of(fen),
+                Some(Outcome::Draw(DrawReason::InsufficientMaterial)),
+                "{fen}"
+            );
+        }
+        let playable = [
+            "8/8/3bk3/8/8/3K4/4B3/8 w - - 0 1", // opposite-coloured bishops
+            "8/8/4kn2/8/8/3KN3/8/8 w - - 0 1",  // KN v KN
+            "8/8/4k3/8/8/3KNN2/8/8 w - - 0 1",  // KNN v K
+            "8/8/4k3/8/8/3K4/4P3/8 w - - 0 1",  // a pawn
+        ];
+        for fen in playable {
+            assert_eq!(outcome_of(fen), None, "{fen}");
+        }
+    }
+
+    #[test]
+    fn outcome_fifty_move_and_repetition() {
+        let fen = "8/8/4k3/8/8/3K4/R7/8 w - - 100 80";
+        assert_eq!(outcome_of(fen), Some(Outcome::Draw(DrawReason::FiftyMove)));
+        // Mate delivered on the 100th half-move still counts as mate
+        let mate = "1k5R/8/1K6/8/8/8/8/8 b - - 100 80";
+        assert_eq!(
+            outcome_of(mate),
+            Some(Outcome::Checkmate {
+                winner: Color::White
+            })
+        );
+
+        let mut game = GameState::new();
+        for uci in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ] {
+            assert!(game.outcome().is_none());
+            game.make_move(crate::uci::parse_uci_move(uci).unwrap());
+        }
+        assert_eq!(game.outcome(), Some(Outcome::Draw(DrawReason::Repetition)));
     }
 
     #[test]
@@ -1,13 +1,25 @@
+pub mod analysis;
 pub mod bot;
+pub mod compete;
+pub mod diagram;
+pub mod epd;
 pub mod eval;
 pub mod game;
+pub mod json;
 pub mod nn;
 pub mod openings;
+pub mod perft;
+pub mod pgn;
+pub mod san;
 pub mod search;
 pub mod uci;
 
-pub use bot::{BaselineBot, Level, ALL_LEVELS};
+pub use bot::{BaselineBot, Level, TimeLeft, ALL_LEVELS};
 pub use cozy_chess::{Board, Color, File, Move, Piece, Rank, Square};
+pub use diagram::board_diagram;
 pub use nn::NnEvalBot;
+pub use san::{format_san, parse_san};
 pub use search::SearchContext;
-pub use uci::{format_move, parse_file, parse_rank, parse_uci_move, piece_unicode};
+pub use uci::{
+    format_move, parse_file, parse_legal_uci_move, parse_rank, parse_uci_move, piece_unicode,
+};
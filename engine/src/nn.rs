@@ -1,4 +1,4 @@
-use cozy_chess::{Board, Color, GameStatus, Piece, Square};
+use cozy_chess::{Board, BoardBuilder, Color, GameStatus, Piece, Rank, Square};
 use ort::session::Session;
 use ort::value::Tensor;
 use std::path::Path;
@@ -11,7 +11,7 @@ use crate::search::capture_moves;
 use crate::Move;
 
 // Piece channel order (matches both current-player and opponent halves)
-const PIECE_TYPES: [Piece; 6] = [
+pub const PIECE_TYPES: [Piece; 6] = [
     Piece::Pawn,
     Piece::Knight,
     Piece::Bishop,
@@ -32,7 +32,7 @@ fn square_idx(sq: Square, flip: bool) -> usize {
 }
 
 /// Number of floats per perspective half: 12 piece planes × 64 squares + 2 castling rights.
-const HALF_SIZE: usize = 770;
+pub const HALF_SIZE: usize = 770;
 
 /// Total tensor size: two perspective halves.
 pub const TENSOR_SIZE: usize = HALF_SIZE * 2; // 1540
@@ -104,6 +104,44 @@ pub fn board_to_tensor(game: &GameState) -> Vec<f32> {
     tensor
 }
 
+/// Index into `board_to_tensor`'s output of `channel` (0–11) at `sq`, in the half
+/// seen by the side to move (`perspective` 0) or the other side (1) when `stm`
+/// is to move.
+pub fn tensor_index(stm: Color, perspective: usize, channel: usize, sq: Square) -> usize {
+    let color = if perspective == 0 { stm } else { !stm };
+    perspective * HALF_SIZE + channel * 64 + square_idx(sq, color == Color::Black)
+}
+
+/// `board` with the piece on `sq` removed, for occlusion. Castling rights of a
+/// removed rook, and the en passant square of a removed pawn that just
+/// double-stepped, are dropped along with it. `None` for
+/// an empty square, a king, or a position cozy-chess rejects.
+pub fn without_piece(board: &Board, sq: Square) -> Option<Board> {
+    let (piece, color) = board.piece_on(sq).zip(board.color_on(sq))?;
+    if piece == Piece::King {
+        return None;
+    }
+    let mut builder = BoardBuilder::from_board(board);
+    *builder.square_mut(sq) = None;
+    if piece == Piece::Rook && sq.rank() == Rank::First.relative_to(color) {
+        let rights = builder.castle_rights_mut(color);
+        if rights.short == Some(sq.file()) {
+            rights.short = None;
+        }
+        if rights.long == Some(sq.file()) {
+            rights.long = None;
+        }
+    }
+    let mover = !board.side_to_move();
+    let double_stepped = builder
+        .en_passant
+        .map(|target| Square::new(target.file(), Rank::Fourth.relative_to(mover)));
+    if piece == Piece::Pawn && double_stepped == Some(sq) {
+        builder.en_passant = None;
+    }
+    builder.build().ok()
+}
+
 // ---------------------------------------------------------------------------
 // Parameter counting via minimal ONNX protobuf parsing
 // ---------------------------------------------------------------------------
@@ -184,6 +222,9 @@ pub fn count_parameters(path: &Path) -> Result<u64, Box<dyn std::error::Error +
 const MATE_SCORE_F: f32 = 100_000.0;
 const DRAW_SCORE_F: f32 = 0.0;
 
+/// A root move, the position it leads to, and that position's stand-pat (if batched).
+type RootChild = (Move, Board, Option<f32>);
+
 /// A chess bot that runs an ONNX scalar evaluation network with depth-1
 /// search plus quiescence (follows captures to quiet positions).
 ///
@@ -196,6 +237,11 @@ pub struct NnEvalBot {
     inference_calls: AtomicU64,
     /// Total positions evaluated across all batch calls.
     positions_evaluated: AtomicU64,
+    /// Root score of the move chosen by the most recent search.
+    last_score: Mutex<Option<f32>>,
+    /// Score root moves that repeat an earlier position of the game as draws.
+    /// Off by default, matching the official levels.
+    pub repetition_aware: bool,
 }
 
 impl NnEvalBot {
@@ -231,7 +277,7 @@ impl NnEvalBot {
                      Your model's output batch dimension may be unnamed/anonymous.\n\
                      Fix: use named dynamic axes in your ONNX export, or use a Linear (Gemm) \
                      final layer instead of MatMul+Add.",
-                    &*shape, raw.len()
+                    shape, raw.len()
                 ).into());
             }
         }
@@ -241,6 +287,8 @@ impl NnEvalBot {
             param_count,
             inference_calls: AtomicU64::new(0),
             positions_evaluated: AtomicU64::new(0),
+            last_score: Mutex::new(None),
+            repetition_aware: false,
         })
     }
 
@@ -258,6 +306,12 @@ impl NnEvalBot {
         )
     }
 
+    /// Score of the move chosen by the most recent search, from the mover's
+    /// perspective in the network's output units (`None` if no search ran).
+    pub fn last_score(&self) -> Option<f32> {
+        self.last_score.lock().ok().and_then(|s| *s)
+    }
+
     /// Evaluate a batch of positions in a single ONNX call.
     /// Each tensor in `tensors` is a flat [1540] encoding.
     /// Returns one scalar eval per position.
@@ -307,6 +361,37 @@ impl NnEvalBot {
         Ok(results[0])
     }
 
+    /// Occlusion saliency: for every piece but the kings, how much the raw
+    /// evaluation drops for the piece's owner when it is taken off the board.
+    /// Pieces whose removal gives an illegal position are left out.
+    pub fn occlusion_saliency(
+        &self,
+        game: &GameState,
+    ) -> Result<Vec<(Square, f32)>, Box<dyn std::error::Error>> {
+        let board = &game.board;
+        let mut squares = Vec::new();
+        let mut tensors = vec![board_to_tensor(game)];
+        for sq in board.occupied() {
+            if let Some(occluded) = without_piece(board, sq) {
+                squares.push(sq);
+                tensors.push(board_to_tensor(&GameState::from_board(occluded)));
+            }
+        }
+        let evals = self.nn_eval_batch(&tensors)?;
+        let base = evals[0];
+        let stm = board.side_to_move();
+        Ok(squares
+            .into_iter()
+            .zip(&evals[1..])
+            .map(|(sq, &eval)| {
+                // Evals are from the side to move's view; flip for the other side's pieces.
+                let drop = base - eval;
+                let owned_by_stm = board.color_on(sq) == Some(stm);
+                (sq, if owned_by_stm { drop } else { -drop })
+            })
+            .collect())
+    }
+
     /// Sequential quiescence search (reference implementation for testing).
     #[cfg(test)]
     fn quiescence_nn_sequential(
@@ -500,19 +585,13 @@ impl NnEvalBot {
         Ok(best_mv)
     }
 
-    /// Depth-1 search with alpha-beta at root + batched quiescence.
-    ///
-    /// Batch-evaluates stand-pats for all root children in one ONNX call,
-    /// then passes pre-computed hints into quiescence search.
-    fn try_choose_move(
+    /// Child positions of the root, each with its batch-evaluated stand-pat if
+    /// it is ongoing and not in check (`None` otherwise).
+    fn root_children(
         &self,
         game: &GameState,
-    ) -> Result<Option<Move>, Box<dyn std::error::Error>> {
-        let legal = game.legal_moves();
-        if legal.is_empty() {
-            return Ok(None);
-        }
-
+        legal: &[Move],
+    ) -> Result<Vec<RootChild>, Box<dyn std::error::Error>> {
         // Make all child boards
         let child_boards: Vec<(Move, Board)> = legal
             .iter()
@@ -532,6 +611,9 @@ impl NnEvalBot {
             if child.status() != GameStatus::Ongoing {
                 continue;
             }
+            if self.repetition_aware && game.is_repetition_after(child) {
+                continue;
+            }
             if !child.checkers().is_empty() {
                 continue;
             }
@@ -546,21 +628,77 @@ impl NnEvalBot {
             }
         }
 
+        Ok(child_boards
+            .into_iter()
+            .zip(hints)
+            .map(|((mv, child), hint)| (mv, child, hint))
+            .collect())
+    }
+
+    /// Score a root child from the mover's perspective with window (`alpha`, +inf).
+    fn root_child_score(
+        &self,
+        game: &GameState,
+        child_board: &Board,
+        alpha: f32,
+        hint: Option<f32>,
+    ) -> Result<f32, Box<dyn std::error::Error>> {
+        Ok(match child_board.status() {
+            GameStatus::Won => MATE_SCORE_F,
+            GameStatus::Drawn => DRAW_SCORE_F,
+            GameStatus::Ongoing
+                if self.repetition_aware && game.is_repetition_after(child_board) =>
+            {
+                DRAW_SCORE_F
+            }
+            GameStatus::Ongoing => {
+                -self.quiescence_nn(child_board, f32::NEG_INFINITY, -alpha, hint)?
+            }
+        })
+    }
+
+    /// Evaluate every legal move with a full window and return them best first
+    /// (mover's perspective, network output units). Slower than `choose_move`,
+    /// which prunes moves that cannot beat the best so far.
+    pub fn score_moves(
+        &self,
+        game: &GameState,
+    ) -> Result<Vec<(Move, f32)>, Box<dyn std::error::Error>> {
+        self.set_last_score(None);
+        let legal = game.legal_moves();
+        let mut scored = Vec::with_capacity(legal.len());
+        for (mv, child_board, hint) in self.root_children(game, &legal)? {
+            let eval = self.root_child_score(game, &child_board, f32::NEG_INFINITY, hint)?;
+            scored.push((mv, eval));
+        }
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        self.set_last_score(scored.first().map(|&(_, eval)| eval));
+        Ok(scored)
+    }
+
+    /// Depth-1 search with alpha-beta at root + batched quiescence.
+    ///
+    /// Batch-evaluates stand-pats for all root children in one ONNX call,
+    /// then passes pre-computed hints into quiescence search.
+    fn try_choose_move(
+        &self,
+        game: &GameState,
+    ) -> Result<Option<Move>, Box<dyn std::error::Error>> {
+        self.set_last_score(None);
+        let legal = game.legal_moves();
+        if legal.is_empty() {
+            return Ok(None);
+        }
+
         let mut best_mv: Option<Move> = None;
         let mut alpha = f32::NEG_INFINITY;
 
-        for (i, (mv, child_board)) in child_boards.iter().enumerate() {
-            let eval = match child_board.status() {
-                GameStatus::Won => MATE_SCORE_F,
-                GameStatus::Drawn => DRAW_SCORE_F,
-                GameStatus::Ongoing => {
-                    -self.quiescence_nn(child_board, f32::NEG_INFINITY, -alpha, hints[i])?
-                }
-            };
+        for (mv, child_board, hint) in self.root_children(game, &legal)? {
+            let eval = self.root_child_score(game, &child_board, alpha, hint)?;
 
             if eval > alpha {
                 alpha = eval;
-                best_mv = Some(*mv);
+                best_mv = Some(mv);
             }
 
             if eval >= MATE_SCORE_F {
@@ -570,12 +708,23 @@ impl NnEvalBot {
 
         if best_mv.is_none() {
             best_mv = legal.into_iter().next();
+        } else {
+            self.set_last_score(Some(alpha));
         }
 
         Ok(best_mv)
     }
+
+    fn set_last_score(&self, score: Option<f32>) {
+        if let Ok(mut last) = self.last_score.lock() {
+            *last = score;
+        }
+    }
 }
 
+/// The NN always searches one ply and its captures, so there is no deeper
+/// iteration to give up when time is short: it keeps the default
+/// `choose_move_timed`, which ignores the clock.
 impl Bot for NnEvalBot {
     fn choose_move(&self, game: &GameState) -> Option<Move> {
         match self.try_choose_move(game) {
@@ -595,7 +744,7 @@ impl Bot for NnEvalBot {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cozy_chess::Board;
+    use cozy_chess::{Board, File};
 
     // ── Encoding tests ──────────────────────────────────────────────────
 
@@ -712,6 +861,49 @@ mod tests {
         assert_eq!(tensor[769], 1.0, "White has queenside castling");
     }
 
+    #[test]
+    fn tensor_index_finds_every_piece() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        ] {
+            let board: Board = fen.parse().unwrap();
+            let tensor = board_to_tensor(&GameState::from_board(board.clone()));
+            let stm = board.side_to_move();
+            for perspective in 0..2 {
+                let own = if perspective == 0 { stm } else { !stm };
+                for sq in board.occupied() {
+                    let piece = board.piece_on(sq).unwrap();
+                    let kind = PIECE_TYPES.iter().position(|&p| p == piece).unwrap();
+                    let channel = if board.color_on(sq) == Some(own) { kind } else { kind + 6 };
+                    let idx = tensor_index(stm, perspective, channel, sq);
+                    assert_eq!(tensor[idx], 1.0, "{fen}: {sq} in half {perspective}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn without_piece_drops_castling_and_kings() {
+        let board = Board::default();
+        assert!(without_piece(&board, Square::E1).is_none());
+        assert!(without_piece(&board, Square::E4).is_none());
+
+        let no_rook = without_piece(&board, Square::H1).unwrap();
+        assert!(no_rook.piece_on(Square::H1).is_none());
+        assert!(no_rook.castle_rights(Color::White).short.is_none());
+        assert!(no_rook.castle_rights(Color::White).long.is_some());
+
+        let board: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+            .parse()
+            .unwrap();
+        let no_pawn = without_piece(&board, Square::E4).unwrap();
+        assert!(no_pawn.en_passant().is_none());
+        // Any other pawn leaves the en passant square alone.
+        let other_pawn = without_piece(&board, Square::D2).unwrap();
+        assert_eq!(other_pawn.en_passant(), Some(File::E));
+    }
+
     // ── Search structure tests (no ONNX needed) ─────────────────────────
 
     #[test]
@@ -842,7 +1034,7 @@ mod tests {
         let count = count_parameters(&path).expect("should parse ONNX");
         // Linear(1540, 1) = 1540 weights. Bias may be folded away by constant folding.
         assert!(
-            count >= 1540 && count <= 1541,
+            (1540..=1541).contains(&count),
             "expected ~1540 params, got {count}"
         );
     }
@@ -906,7 +1098,7 @@ mod tests {
             .collect();
 
         // Batch eval
-        let tensors: Vec<Vec<f32>> = positions.iter().map(|g| board_to_tensor(g)).collect();
+        let tensors: Vec<Vec<f32>> = positions.iter().map(board_to_tensor).collect();
         let batch = bot.nn_eval_batch(&tensors).unwrap();
 
         assert_eq!(individual.len(), batch.len());
@@ -984,6 +1176,7 @@ mod tests {
             GameStatus::Won,
             "NN bot should find checkmate when available"
         );
+        assert_eq!(bot.last_score(), Some(MATE_SCORE_F));
     }
 
     #[test]
@@ -1,20 +1,110 @@
+use crate::epd::parse_epd;
 use crate::game::GameState;
 use std::path::Path;
 
-/// Load opening FENs from a text file (one per line, `#` comments and blank lines skipped).
-pub fn load_opening_fens(path: &Path) -> Result<Vec<String>, String> {
+/// One opening position, with its name and ECO code when the file provides them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningEntry {
+    pub fen: String,
+    pub name: Option<String>,
+    pub eco: Option<String>,
+    /// Section of the openings file the entry sits under, e.g. "Open Games (1.e4 e5)".
+    pub family: Option<String>,
+}
+
+impl OpeningEntry {
+    /// Display label, e.g. "C50 Italian Game", or `None` for an unlabelled FEN.
+    pub fn label(&self) -> Option<String> {
+        match (&self.eco, &self.name) {
+            (Some(eco), Some(name)) => Some(format!("{eco} {name}")),
+            (Some(eco), None) => Some(eco.clone()),
+            (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Opening family for grouping results: the file section, or "Other".
+    pub fn family(&self) -> &str {
+        self.family.as_deref().unwrap_or("Other")
+    }
+}
+
+/// Name of a section header comment such as `# ── Open Games (1.e4 e5) ───`.
+pub fn section_header(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix('#')?.trim_start();
+    if !rest.starts_with('─') {
+        return None;
+    }
+    let name = rest.trim_matches(|c: char| c == '─' || c.is_whitespace());
+    (!name.is_empty()).then_some(name)
+}
+
+/// Parse one non-comment line of an openings file.
+///
+/// Accepts either a full FEN (six fields) or an EPD record whose `id` and `eco`
+/// opcodes label the opening, e.g. `<4 fields> id "Italian Game"; eco "C50"; hmvc 3; fmvn 3;`.
+pub fn parse_opening_line(line: &str) -> Result<OpeningEntry, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let is_fen = fields.len() == 6 && fields[4..].iter().all(|f| f.parse::<u32>().is_ok());
+    if is_fen {
+        return Ok(OpeningEntry {
+            fen: fields.join(" "),
+            name: None,
+            eco: None,
+            family: None,
+        });
+    }
+    let epd = parse_epd(line)?;
+    let eco = epd
+        .op("eco")
+        .and_then(|operands| operands.first())
+        .map(|s| s.to_string());
+    Ok(OpeningEntry {
+        fen: epd.to_fen(),
+        name: epd.id().map(|s| s.to_string()),
+        eco,
+        family: None,
+    })
+}
+
+/// Load opening entries from a text file (one FEN or EPD per line, `#` comments
+/// and blank lines skipped). Entries take their family from the section header
+/// above them.
+pub fn load_opening_entries(path: &Path) -> Result<Vec<OpeningEntry>, String> {
     let contents = std::fs::read_to_string(path)
         .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
-    let fens: Vec<String> = contents
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty() && !l.starts_with('#'))
-        .map(|l| l.to_string())
-        .collect();
-    if fens.is_empty() {
+    let entries = parse_opening_entries(&contents)?;
+    if entries.is_empty() {
         return Err(format!("No FENs found in {}", path.display()));
     }
-    Ok(fens)
+    Ok(entries)
+}
+
+/// Parse the contents of an openings file; see `load_opening_entries`.
+pub fn parse_opening_entries(contents: &str) -> Result<Vec<OpeningEntry>, String> {
+    let mut family = None;
+    let mut entries = Vec::new();
+    for line in contents.lines().map(|l| l.trim()) {
+        if let Some(name) = section_header(line) {
+            family = Some(name.to_string());
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut entry = parse_opening_line(line)
+            .map_err(|e| format!("Opening #{} invalid: {}", entries.len() + 1, e))?;
+        entry.family = family.clone();
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Load opening FENs from a text file (one per line, `#` comments and blank lines skipped).
+pub fn load_opening_fens(path: &Path) -> Result<Vec<String>, String> {
+    Ok(load_opening_entries(path)?
+        .into_iter()
+        .map(|e| e.fen)
+        .collect())
 }
 
 /// Load opening FENs and parse each into a GameState (validates all FENs).
@@ -51,4 +141,50 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn bundled_openings_are_labelled() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/openings.txt");
+        let entries = load_opening_entries(&path).unwrap();
+        for (i, entry) in entries.iter().enumerate() {
+            assert!(entry.name.is_some(), "opening #{} has no name", i + 1);
+            assert!(entry.eco.is_some(), "opening #{} has no ECO code", i + 1);
+        }
+        let family = |name: &str| entries.iter().find(|e| e.name.as_deref() == Some(name));
+        assert_eq!(family("French Defense").unwrap().family(), "Semi-Open Games (1.e4, not e5)");
+        assert_eq!(family("Dutch Defense").unwrap().family(), "Indian Defenses (1.d4 Nf6)");
+        assert_eq!(family("Evans Gambit").unwrap().family(), "Other");
+    }
+
+    #[test]
+    fn entries_take_the_family_of_their_section() {
+        let text = "\
+# Header comment
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+# ── Flank Openings ──────
+# 22. English Opening: 1.c4
+rnbqkbnr/pppppppp/8/8/2P5/8/PP1PPPPP/RNBQKBNR b KQkq - id \"English\"; eco \"A10\";
+";
+        let entries = parse_opening_entries(text).unwrap();
+        assert_eq!(entries[0].family, None);
+        assert_eq!(entries[0].family(), "Other");
+        assert_eq!(entries[1].family(), "Flank Openings");
+        assert_eq!(section_header("# 22. English Opening: 1.c4"), None);
+    }
+
+    #[test]
+    fn parse_line_fen_and_epd() {
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4PP2/8/PPPP2PP/RNBQKBNR b KQkq f3 0 2";
+        let entry = parse_opening_line(fen).unwrap();
+        assert_eq!(entry.fen, fen);
+        assert_eq!(entry.name, None);
+
+        let epd = "rnbqkbnr/pppp1ppp/8/4p3/4PP2/8/PPPP2PP/RNBQKBNR b KQkq f3 id \"King's Gambit\"; eco \"C30\"; fmvn 2;";
+        let entry = parse_opening_line(epd).unwrap();
+        assert_eq!(entry.fen, fen);
+        assert_eq!(entry.name.as_deref(), Some("King's Gambit"));
+        assert_eq!(entry.label().as_deref(), Some("C30 King's Gambit"));
+        assert_eq!(entry.family(), "Other");
+        assert!(GameState::from_fen(&entry.fen).is_ok());
+    }
 }
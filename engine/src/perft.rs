@@ -0,0 +1,226 @@
+use cozy_chess::{Board, Move};
+
+use crate::game::GameState;
+use crate::uci::format_move;
+
+/// A reference position with known perft node counts.
+pub struct PerftCase {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// `(depth, nodes)` pairs in increasing depth.
+    pub counts: &'static [(u32, u64)],
+}
+
+/// Standard perft positions (chessprogramming.org "Perft Results").
+pub const REFERENCE_POSITIONS: &[PerftCase] = &[
+    PerftCase {
+        name: "startpos",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        counts: &[(1, 20), (2, 400), (3, 8_902), (4, 197_281), (5, 4_865_609)],
+    },
+    PerftCase {
+        name: "kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        counts: &[(1, 48), (2, 2_039), (3, 97_862), (4, 4_085_603)],
+    },
+    PerftCase {
+        name: "position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        counts: &[(1, 14), (2, 191), (3, 2_812), (4, 43_238), (5, 674_624)],
+    },
+    PerftCase {
+        name: "position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        counts: &[(1, 6), (2, 264), (3, 9_467), (4, 422_333)],
+    },
+    PerftCase {
+        name: "position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        counts: &[(1, 44), (2, 1_486), (3, 62_379), (4, 2_103_487)],
+    },
+    PerftCase {
+        name: "position 6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        counts: &[(1, 46), (2, 2_079), (3, 89_890), (4, 3_894_594)],
+    },
+];
+
+fn legal_moves(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.generate_moves(|piece_moves| {
+        moves.extend(piece_moves);
+        false
+    });
+    moves
+}
+
+/// Count leaf nodes of the legal move tree to `depth` (bulk-counted at the last ply).
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        let mut count = 0;
+        board.generate_moves(|piece_moves| {
+            count += piece_moves.len() as u64;
+            false
+        });
+        return count;
+    }
+    legal_moves(board)
+        .into_iter()
+        .map(|mv| {
+            let mut child = board.clone();
+            child.play_unchecked(mv);
+            perft(&child, depth - 1)
+        })
+        .sum()
+}
+
+/// Per-root-move node counts, in move generation order. Empty at depth 0,
+/// where the only node is the root itself.
+pub fn perft_divide(board: &Board, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    legal_moves(board)
+        .into_iter()
+        .map(|mv| {
+            let mut child = board.clone();
+            child.play_unchecked(mv);
+            (mv, perft(&child, depth - 1))
+        })
+        .collect()
+}
+
+/// Like `perft`, but at every node also checks that:
+/// - the incrementally updated hash matches the hash of the position re-parsed from its FEN,
+/// - the FEN round-trips,
+/// - `GameState::make_move` accepts the move and reaches the same board as `play_unchecked`,
+/// - the position history grows by the new hash and the repetition count agrees with it,
+/// - `GameState::undo_move` restores the board, history and repetition count.
+///
+/// Returns the node count, or a description of the first inconsistency.
+pub fn perft_checked(game: &GameState, depth: u32) -> Result<u64, String> {
+    let board = &game.board;
+    let fen = board.to_string();
+    let reparsed: Board = fen
+        .parse()
+        .map_err(|e| format!("FEN does not re-parse: {fen} ({e:?})"))?;
+    if reparsed.hash() != board.hash() {
+        return Err(format!("hash differs from re-parsed FEN: {fen}"));
+    }
+    if reparsed.to_string() != fen {
+        return Err(format!("FEN does not round-trip: {fen} -> {reparsed}"));
+    }
+    if depth == 0 {
+        return Ok(1);
+    }
+
+    let mut nodes = 0;
+    for mv in legal_moves(board) {
+        let mut expected = board.clone();
+        expected.play_unchecked(mv);
+
+        let mut child = game.clone();
+        if !child.make_move(mv) {
+            return Err(format!("make_move rejected legal move {} in {fen}", format_move(mv)));
+        }
+        if child.board != expected {
+            return Err(format!(
+                "make_move({}) reached {} but play_unchecked reached {} from {fen}",
+                format_move(mv),
+                child.board,
+                expected
+            ));
+        }
+        check_repetition_bookkeeping(game, &child, mv)?;
+        nodes += perft_checked(&child, depth - 1)?;
+    }
+    Ok(nodes)
+}
+
+/// `child` is `game` after `mv`: compare its position history and repetition
+/// count with the parent's, and check that undoing `mv` gets the parent back.
+fn check_repetition_bookkeeping(
+    game: &GameState,
+    child: &GameState,
+    mv: Move,
+) -> Result<(), String> {
+    let fen = game.board.to_string();
+    let uci = format_move(mv);
+    let history = game.position_history();
+    let hash = child.board.hash();
+    if child.position_history() != [history, &[hash]].concat().as_slice() {
+        return Err(format!("position history not extended by {uci} from {fen}"));
+    }
+    let occurrences = history.iter().filter(|&&h| h == hash).count() as u32 + 1;
+    if child.repetitions() != occurrences {
+        return Err(format!(
+            "repetition count {} after {uci} from {fen}, history has {occurrences}",
+            child.repetitions()
+        ));
+    }
+
+    let mut undone = child.clone();
+    if undone.undo_move() != Some(mv) {
+        return Err(format!("undo_move did not return {uci} from {fen}"));
+    }
+    if undone.board != game.board
+        || undone.history != game.history
+        || undone.position_history() != history
+        || undone.repetitions() != game.repetitions()
+    {
+        return Err(format!("undo_move({uci}) did not restore {fen}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keep debug-build test time down: only check counts up to this many nodes.
+    const MAX_TEST_NODES: u64 = 100_000;
+
+    #[test]
+    fn reference_positions_match() {
+        for case in REFERENCE_POSITIONS {
+            let board: Board = case.fen.parse().unwrap();
+            for &(depth, expected) in case.counts {
+                if expected > MAX_TEST_NODES {
+                    continue;
+                }
+                assert_eq!(perft(&board, depth), expected, "{} depth {}", case.name, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let case = &REFERENCE_POSITIONS[1];
+        let board: Board = case.fen.parse().unwrap();
+        let divide = perft_divide(&board, 2);
+        assert_eq!(divide.len() as u64, case.counts[0].1);
+        assert_eq!(divide.iter().map(|(_, n)| n).sum::<u64>(), case.counts[1].1);
+        assert!(perft_divide(&board, 0).is_empty());
+    }
+
+    #[test]
+    fn checked_perft_agrees() {
+        for case in REFERENCE_POSITIONS {
+            let game = GameState::from_fen(case.fen).unwrap();
+            let (depth, expected) = case.counts[1];
+            assert_eq!(perft_checked(&game, depth), Ok(expected), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn checked_perft_follows_repetitions() {
+        // Knights out and back twice: the start position has occurred three times
+        let moves = ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"];
+        let game = GameState::from_fen_and_moves(REFERENCE_POSITIONS[0].fen, &moves).unwrap();
+        assert_eq!(game.repetitions(), 3);
+        assert_eq!(perft_checked(&game, 3), Ok(REFERENCE_POSITIONS[0].counts[2].1));
+    }
+}
@@ -0,0 +1,224 @@
+/// A game read from PGN: its tag pairs and mainline SAN moves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// Mainline moves in SAN, with move numbers, comments, NAGs and variations removed.
+    pub moves: Vec<String>,
+    /// Game termination marker ("1-0", "0-1", "1/2-1/2" or "*"), if present.
+    pub result: Option<String>,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Strip a leading move number ("12." / "12...") glued to a move, leaving
+/// "0-0" castling alone.
+pub fn strip_move_number(token: &str) -> &str {
+    let rest = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.starts_with('.') {
+        rest.trim_start_matches('.')
+    } else {
+        token
+    }
+}
+
+/// Add mainline tokens from a chunk of movetext, tracking comment and variation nesting.
+fn push_movetext(text: &str, game: &mut PgnGame, in_comment: &mut bool, depth: &mut usize) {
+    let mut token = String::new();
+    let flush = |token: &mut String, game: &mut PgnGame| {
+        if token.is_empty() {
+            return;
+        }
+        let t = std::mem::take(token);
+        if is_result(&t) {
+            game.result = Some(t);
+            return;
+        }
+        let t = strip_move_number(&t);
+        if t.is_empty() || t.starts_with('$') {
+            return;
+        }
+        game.moves.push(t.to_string());
+    };
+
+    for c in text.chars() {
+        if *in_comment {
+            if c == '}' {
+                *in_comment = false;
+            }
+            continue;
+        }
+        match c {
+            '{' => {
+                flush(&mut token, game);
+                *in_comment = true;
+            }
+            ';' => {
+                // Rest-of-line comment
+                flush(&mut token, game);
+                return;
+            }
+            '(' => {
+                flush(&mut token, game);
+                *depth += 1;
+            }
+            ')' => {
+                token.clear();
+                *depth = depth.saturating_sub(1);
+            }
+            c if c.is_whitespace() => {
+                if *depth == 0 {
+                    flush(&mut token, game);
+                } else {
+                    token.clear();
+                }
+            }
+            c => {
+                if *depth == 0 {
+                    token.push(c);
+                }
+            }
+        }
+    }
+    if *depth == 0 {
+        flush(&mut token, game);
+    }
+}
+
+/// Parse every game in a PGN document. Games without moves or tags are skipped.
+pub fn parse_pgn(text: &str) -> Vec<PgnGame> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+    let mut in_comment = false;
+    let mut depth = 0usize;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if !in_comment && trimmed.starts_with('[') {
+            // A tag after movetext starts the next game
+            if !game.moves.is_empty() || game.result.is_some() {
+                games.push(std::mem::take(&mut game));
+                depth = 0;
+            }
+            if let Some(tag) = parse_tag(trimmed) {
+                game.tags.push(tag);
+            }
+            continue;
+        }
+        if !in_comment && trimmed.starts_with('%') {
+            continue; // escape line
+        }
+        push_movetext(line, &mut game, &mut in_comment, &mut depth);
+        if game.result.is_some() && depth == 0 && !in_comment {
+            games.push(std::mem::take(&mut game));
+        }
+    }
+    if !game.moves.is_empty() || !game.tags.is_empty() {
+        games.push(game);
+    }
+    games
+}
+
+/// Quote a PGN tag value.
+pub fn quote_tag_value(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Join movetext tokens with spaces, wrapping lines at 80 columns.
+pub fn wrap_movetext(tokens: &[String]) -> String {
+    let mut out = String::new();
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > 80 {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(token);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tags_moves_and_result() {
+        let text = r#"[Event "Test"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+
+1. e4 {best by test} e5 2. Nf3 $1 Nc6 (2... d6 3. d4) 3. Bb5 a6 1-0
+"#;
+        let games = parse_pgn(text);
+        assert_eq!(games.len(), 1);
+        let game = &games[0];
+        assert_eq!(game.tag("Event"), Some("Test"));
+        assert_eq!(game.moves, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(game.result.as_deref(), Some("1-0"));
+    }
+
+    #[test]
+    fn multiple_games_and_multiline_comments() {
+        let text = r#"[Event "One"]
+
+1.d4 d5 {a comment
+spanning lines} 2.c4 *
+
+[Event "Two"]
+
+1.e4 c5 ; rest of line ignored Nf3
+2.Nf3 1/2-1/2
+"#;
+        let games = parse_pgn(text);
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].moves, ["d4", "d5", "c4"]);
+        assert_eq!(games[1].tag("Event"), Some("Two"));
+        assert_eq!(games[1].moves, ["e4", "c5", "Nf3"]);
+        assert_eq!(games[1].result.as_deref(), Some("1/2-1/2"));
+    }
+
+    #[test]
+    fn bare_movetext_without_tags() {
+        let games = parse_pgn("1. e4 e5 2. Nf3");
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves, ["e4", "e5", "Nf3"]);
+        assert_eq!(games[0].result, None);
+    }
+
+    #[test]
+    fn strips_move_numbers_but_not_castling() {
+        assert_eq!(strip_move_number("12.Nf3"), "Nf3");
+        assert_eq!(strip_move_number("3...0-0"), "0-0");
+        assert_eq!(strip_move_number("0-0"), "0-0");
+        assert_eq!(strip_move_number("0-0-0"), "0-0-0");
+        assert_eq!(strip_move_number("4."), "");
+    }
+}
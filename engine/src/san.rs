@@ -0,0 +1,261 @@
+use cozy_chess::{Board, GameStatus, Move, Piece};
+
+fn piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "",
+        Piece::Knight => "N",
+        Piece::Bishop => "B",
+        Piece::Rook => "R",
+        Piece::Queen => "Q",
+        Piece::King => "K",
+    }
+}
+
+fn legal_moves(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    board.generate_moves(|piece_moves| {
+        moves.extend(piece_moves);
+        false
+    });
+    moves
+}
+
+/// Castling is encoded by cozy-chess as "king captures own rook".
+pub(crate) fn is_castle(board: &Board, mv: Move) -> bool {
+    board.piece_on(mv.from) == Some(Piece::King)
+        && board.color_on(mv.to) == Some(board.side_to_move())
+}
+
+/// Format a legal move in Standard Algebraic Notation (e.g. "Nf3", "exd5", "O-O", "e8=Q+").
+pub fn format_san(board: &Board, mv: Move) -> String {
+    let piece = board.piece_on(mv.from).unwrap_or(Piece::Pawn);
+    let mut san = String::new();
+
+    if is_castle(board, mv) {
+        san.push_str(if mv.to.file() > mv.from.file() { "O-O" } else { "O-O-O" });
+    } else {
+        let is_capture = board.piece_on(mv.to).is_some()
+            || (piece == Piece::Pawn && mv.from.file() != mv.to.file());
+
+        if piece == Piece::Pawn {
+            if is_capture {
+                san.push_str(&mv.from.file().to_string());
+            }
+        } else {
+            san.push_str(piece_letter(piece));
+
+            // Disambiguate against other pieces of the same type reaching the same square
+            let rivals: Vec<Move> = legal_moves(board)
+                .into_iter()
+                .filter(|m| {
+                    m.to == mv.to && m.from != mv.from && board.piece_on(m.from) == Some(piece)
+                })
+                .collect();
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|m| m.from.file() == mv.from.file());
+                let same_rank = rivals.iter().any(|m| m.from.rank() == mv.from.rank());
+                if !same_file {
+                    san.push_str(&mv.from.file().to_string());
+                } else if !same_rank {
+                    san.push_str(&mv.from.rank().to_string());
+                } else {
+                    san.push_str(&mv.from.to_string());
+                }
+            }
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+
+        if let Some(promo) = mv.promotion {
+            san.push('=');
+            san.push_str(piece_letter(promo));
+        }
+    }
+
+    let mut after = board.clone();
+    after.play_unchecked(mv);
+    if !after.checkers().is_empty() {
+        san.push(if after.status() == GameStatus::Won { '#' } else { '+' });
+    }
+    san
+}
+
+fn parse_piece_letter(c: char) -> Option<Piece> {
+    match c {
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+/// Parse a SAN move (e.g. "Nf3", "exd5", "O-O", "e8=Q+") and resolve it to the
+/// matching legal move in `board`. Check marks and annotations are ignored.
+pub fn parse_san(board: &Board, san: &str) -> Option<Move> {
+    let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+    let legal = legal_moves(board);
+
+    let castle = match san {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+    if let Some(short) = castle {
+        return legal.into_iter().find(|&mv| {
+            is_castle(board, mv) && (mv.to.file() > mv.from.file()) == short
+        });
+    }
+
+    let mut chars: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '-').collect();
+    let piece = match chars.first().copied().and_then(parse_piece_letter) {
+        Some(p) => {
+            chars.remove(0);
+            p
+        }
+        None => Piece::Pawn,
+    };
+
+    // Promotion: "e8=Q", or "e8Q" without the '='
+    let mut promotion = None;
+    if piece == Piece::Pawn {
+        if let Some(&last) = chars.last() {
+            if let Some(p) = parse_piece_letter(last) {
+                promotion = Some(p);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+    }
+    chars.retain(|&c| c != '=');
+    if chars.len() < 2 {
+        return None;
+    }
+
+    let to_str: String = chars[chars.len() - 2..].iter().collect();
+    let to: cozy_chess::Square = to_str.parse().ok()?;
+    let hint = &chars[..chars.len() - 2];
+    let mut from_file = None;
+    let mut from_rank = None;
+    for &c in hint {
+        if let Ok(f) = c.to_string().parse::<cozy_chess::File>() {
+            from_file = Some(f);
+        } else if let Ok(r) = c.to_string().parse::<cozy_chess::Rank>() {
+            from_rank = Some(r);
+        } else {
+            return None;
+        }
+    }
+
+    let mut matches = legal.into_iter().filter(|&mv| {
+        mv.to == to
+            && mv.promotion == promotion
+            && board.piece_on(mv.from) == Some(piece)
+            && !is_castle(board, mv)
+            && from_file.is_none_or(|f| mv.from.file() == f)
+            && from_rank.is_none_or(|r| mv.from.rank() == r)
+    });
+    let mv = matches.next()?;
+    if matches.next().is_some() {
+        return None; // ambiguous
+    }
+    Some(mv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci::parse_uci_move;
+
+    fn san(fen: &str, uci: &str) -> String {
+        let board: Board = fen.parse().unwrap();
+        format_san(&board, parse_uci_move(uci).unwrap())
+    }
+
+    #[test]
+    fn pawn_and_piece_moves() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, "e2e4"), "e4");
+        assert_eq!(san(start, "g1f3"), "Nf3");
+    }
+
+    #[test]
+    fn captures_and_en_passant() {
+        // Scandinavian: 1.e4 d5, white to move
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(fen, "e4d5"), "exd5");
+        let ep = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        assert_eq!(san(ep, "e5f6"), "exf6");
+    }
+
+    #[test]
+    fn castling() {
+        let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, "e1h1"), "O-O");
+        assert_eq!(san(fen, "e1a1"), "O-O-O");
+    }
+
+    #[test]
+    fn disambiguation() {
+        // Knights on b1 and f3 can both reach d2
+        let fen = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(san(fen, "b1d2"), "Nbd2");
+        // Rooks on a1 and a5 share a file
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1a3"), "R1a3");
+    }
+
+    #[test]
+    fn promotion_check_and_mate() {
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "a8=Q+");
+        // Fool's mate
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+        assert_eq!(san(fen, "d8h4"), "Qh4#");
+    }
+
+    #[test]
+    fn parse_round_trips_format() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/pP3ppp/8/3Pp3/8/5N2/PPP2PPP/RN2K1NR w KQkq e6 0 1",
+            "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1",
+        ];
+        for fen in fens {
+            let board: Board = fen.parse().unwrap();
+            for mv in legal_moves(&board) {
+                let text = format_san(&board, mv);
+                assert_eq!(parse_san(&board, &text), Some(mv), "{fen}: {text}");
+            }
+        }
+    }
+
+    #[test]
+    fn parse_lenient_forms() {
+        let board = Board::default();
+        let nf3 = parse_uci_move("g1f3").unwrap();
+        assert_eq!(parse_san(&board, "Nf3!?"), Some(nf3));
+        assert_eq!(parse_san(&board, "Ngf3"), Some(nf3));
+        assert_eq!(parse_san(&board, "Ng1-f3"), Some(nf3));
+        let castles: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+        assert_eq!(parse_san(&castles, "0-0"), parse_uci_move("e1h1"));
+        let promo: Board = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(parse_san(&promo, "a8Q"), parse_uci_move("a7a8q"));
+    }
+
+    #[test]
+    fn parse_rejects_illegal_and_ambiguous() {
+        let board = Board::default();
+        assert_eq!(parse_san(&board, "e5"), None);
+        assert_eq!(parse_san(&board, "Nd4"), None);
+        assert_eq!(parse_san(&board, "xyz"), None);
+        // Both knights can reach d2
+        let amb: Board = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1".parse().unwrap();
+        assert_eq!(parse_san(&amb, "Nd2"), None);
+    }
+}
@@ -1,10 +1,88 @@
 use cozy_chess::{Board, GameStatus, Move, Piece};
+use std::cell::Cell;
 
 use crate::eval::evaluate;
 
-const MATE_SCORE: i32 = 100_000;
+pub const MATE_SCORE: i32 = 100_000;
 const DRAW_SCORE: i32 = 0;
 
+// ---------------------------------------------------------------------------
+// Node counting
+// ---------------------------------------------------------------------------
+
+thread_local! {
+    static NODES: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count_node() {
+    NODES.with(|n| n.set(n.get() + 1));
+}
+
+/// Total nodes visited by the search functions on the current thread.
+/// Take the difference of two readings to measure a single search.
+pub fn nodes_searched() -> u64 {
+    NODES.with(|n| n.get())
+}
+
+// ---------------------------------------------------------------------------
+// Repetition detection
+// ---------------------------------------------------------------------------
+
+/// Hashes of the positions leading to the current search node (game history,
+/// then the search path), oldest first. When enabled, a node that repeats one of
+/// them is scored as a draw.
+struct RepetitionPath {
+    hashes: Vec<u64>,
+    enabled: bool,
+    /// Repetition draws scored so far. A node whose subtree added any has a
+    /// path-dependent score, so it stays out of the transposition table.
+    draws: u64,
+}
+
+impl RepetitionPath {
+    fn off() -> Self {
+        RepetitionPath {
+            hashes: Vec::new(),
+            enabled: false,
+            draws: 0,
+        }
+    }
+
+    fn from_history(history: &[u64]) -> Self {
+        RepetitionPath {
+            hashes: history.to_vec(),
+            enabled: true,
+            draws: 0,
+        }
+    }
+
+    /// True if `board` repeats a position since the last capture or pawn move.
+    fn is_repeat(&mut self, board: &Board) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let hash = board.hash();
+        let window = board.halfmove_clock() as usize;
+        let repeat = self.hashes.iter().rev().take(window).any(|&h| h == hash);
+        if repeat {
+            self.draws += 1;
+        }
+        repeat
+    }
+
+    fn push(&mut self, board: &Board) {
+        if self.enabled {
+            self.hashes.push(board.hash());
+        }
+    }
+
+    fn pop(&mut self) {
+        if self.enabled {
+            self.hashes.pop();
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Transposition table
 // ---------------------------------------------------------------------------
@@ -132,7 +210,7 @@ pub fn capture_moves(board: &Board) -> Vec<Move> {
                 Some(_) => 3,
                 None => 0,
             };
-            let victim = board.piece_on(mv.to).map_or(0, |p| piece_val(p));
+            let victim = board.piece_on(mv.to).map_or(0, piece_val);
             let attacker = piece_val(board.piece_on(mv.from).unwrap());
             (promo_val + victim, std::cmp::Reverse(attacker))
         };
@@ -164,6 +242,7 @@ fn ordered_moves_classic(board: &Board) -> Vec<Move> {
 // ===========================================================================
 
 pub fn quiescence_classic(board: &Board, mut alpha: i32, beta: i32) -> i32 {
+    count_node();
     match board.status() {
         GameStatus::Won => return -MATE_SCORE,
         GameStatus::Drawn => return DRAW_SCORE,
@@ -193,42 +272,78 @@ pub fn quiescence_classic(board: &Board, mut alpha: i32, beta: i32) -> i32 {
     alpha
 }
 
-pub fn negamax_classic(board: &Board, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+pub fn negamax_classic(board: &Board, depth: u32, alpha: i32, beta: i32) -> i32 {
+    negamax_classic_path(board, depth, alpha, beta, &mut RepetitionPath::off())
+}
+
+fn negamax_classic_path(
+    board: &Board,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    path: &mut RepetitionPath,
+) -> i32 {
     match board.status() {
         GameStatus::Won => return -MATE_SCORE,
         GameStatus::Drawn => return DRAW_SCORE,
         GameStatus::Ongoing => {}
     }
+    if path.is_repeat(board) {
+        return DRAW_SCORE;
+    }
 
     if depth == 0 {
         return quiescence_classic(board, alpha, beta);
     }
+    count_node();
 
     let moves = ordered_moves_classic(board);
 
+    path.push(board);
     for mv in moves {
         let mut child = board.clone();
         child.play_unchecked(mv);
-        let score = -negamax_classic(&child, depth - 1, -beta, -alpha);
+        let score = -negamax_classic_path(&child, depth - 1, -beta, -alpha, path);
         if score >= beta {
+            path.pop();
             return beta;
         }
         if score > alpha {
             alpha = score;
         }
     }
+    path.pop();
 
     alpha
 }
 
 pub fn best_move_with_scores_classic(board: &Board, depth: u32) -> Vec<(Move, i32)> {
+    best_move_with_scores_classic_path(board, depth, &mut RepetitionPath::off())
+}
+
+/// Like `best_move_with_scores_classic`, but any position that repeats one in
+/// `history` (the game's position hashes, oldest first, ending with `board`) or
+/// earlier on the search path scores as a draw.
+pub fn best_move_with_scores_classic_in_game(
+    board: &Board,
+    depth: u32,
+    history: &[u64],
+) -> Vec<(Move, i32)> {
+    best_move_with_scores_classic_path(board, depth, &mut RepetitionPath::from_history(history))
+}
+
+fn best_move_with_scores_classic_path(
+    board: &Board,
+    depth: u32,
+    path: &mut RepetitionPath,
+) -> Vec<(Move, i32)> {
     let moves = ordered_moves_classic(board);
     let mut results = Vec::new();
 
     for mv in moves {
         let mut child = board.clone();
         child.play_unchecked(mv);
-        let score = -negamax_classic(&child, depth - 1, -MATE_SCORE, MATE_SCORE);
+        let score = -negamax_classic_path(&child, depth - 1, -MATE_SCORE, MATE_SCORE, path);
         results.push((mv, score));
     }
 
@@ -243,6 +358,7 @@ pub struct SearchContext {
     tt: TTable,
     killers: Vec<[Option<Move>; 2]>,
     history: Box<[[i32; 64]; 64]>,
+    path: RepetitionPath,
 }
 
 impl SearchContext {
@@ -251,6 +367,7 @@ impl SearchContext {
             tt: TTable::new(20),
             killers: vec![[None; 2]; 64],
             history: Box::new([[0i32; 64]; 64]),
+            path: RepetitionPath::off(),
         }
     }
 
@@ -295,10 +412,14 @@ fn negamax_enhanced(
         GameStatus::Drawn => return DRAW_SCORE,
         GameStatus::Ongoing => {}
     }
+    if ctx.path.is_repeat(board) {
+        return DRAW_SCORE;
+    }
 
     let orig_alpha = alpha;
     let hash = board.hash();
     let mut tt_move = None;
+    let draws_before = ctx.path.draws;
 
     if let Some(entry) = ctx.tt.probe(hash) {
         tt_move = entry.best_move;
@@ -322,6 +443,7 @@ fn negamax_enhanced(
     if depth == 0 {
         return quiescence_classic(board, alpha, beta);
     }
+    count_node();
 
     // Null move pruning (R=2)
     if allow_null && depth >= 3 && can_null_move(board) {
@@ -348,6 +470,7 @@ fn negamax_enhanced(
     let mut best_score = i32::MIN;
     let mut best_move = moves[0];
 
+    ctx.path.push(board);
     for (i, &mv) in moves.iter().enumerate() {
         let mut child = board.clone();
         child.play_unchecked(mv);
@@ -379,6 +502,7 @@ fn negamax_enhanced(
             break;
         }
     }
+    ctx.path.pop();
 
     let flag = if best_score >= beta {
         TTFlag::LowerBound
@@ -387,7 +511,9 @@ fn negamax_enhanced(
     } else {
         TTFlag::Exact
     };
-    ctx.tt.store(hash, depth, best_score, flag, Some(best_move));
+    if ctx.path.draws == draws_before {
+        ctx.tt.store(hash, depth, best_score, flag, Some(best_move));
+    }
 
     best_score
 }
@@ -413,3 +539,18 @@ pub fn best_move_with_scores_enhanced(
 
     results
 }
+
+/// Like `best_move_with_scores_enhanced`, but any position that repeats one in
+/// `history` (the game's position hashes, oldest first, ending with `board`) or
+/// earlier on the search path scores as a draw.
+pub fn best_move_with_scores_enhanced_in_game(
+    ctx: &mut SearchContext,
+    board: &Board,
+    depth: u32,
+    history: &[u64],
+) -> Vec<(Move, i32)> {
+    ctx.path = RepetitionPath::from_history(history);
+    let results = best_move_with_scores_enhanced(ctx, board, depth);
+    ctx.path = RepetitionPath::off();
+    results
+}
@@ -1,4 +1,4 @@
-use crate::{Color, File, Move, Piece, Rank, Square};
+use crate::{Board, Color, File, Move, Piece, Rank, Square};
 
 /// Format a move in UCI notation (e.g. "e2e4", "e7e8q").
 pub fn format_move(mv: Move) -> String {
@@ -65,6 +65,35 @@ pub fn parse_uci_move(s: &str) -> Option<Move> {
     })
 }
 
+/// Parse a UCI move and resolve it against the legal moves of `board`.
+///
+/// Castling is accepted both as cozy-chess encodes it (king captures own rook,
+/// "e1h1") and in standard UCI form (king moves two squares, "e1g1").
+pub fn parse_legal_uci_move(board: &Board, s: &str) -> Option<Move> {
+    let mv = parse_uci_move(s.trim())?;
+    if board.is_legal(mv) {
+        return Some(mv);
+    }
+    let is_king = board.piece_on(mv.from) == Some(Piece::King)
+        && board.color_on(mv.from) == Some(board.side_to_move());
+    if is_king && mv.from.rank() == mv.to.rank() && mv.promotion.is_none() {
+        let file_delta = mv.to.file() as i32 - mv.from.file() as i32;
+        if file_delta.abs() == 2 {
+            let rights = board.castle_rights(board.side_to_move());
+            let rook_file = if file_delta > 0 { rights.short } else { rights.long }?;
+            let castle = Move {
+                from: mv.from,
+                to: Square::new(rook_file, mv.from.rank()),
+                promotion: None,
+            };
+            if board.is_legal(castle) {
+                return Some(castle);
+            }
+        }
+    }
+    None
+}
+
 pub fn piece_unicode(piece: Piece, color: Color) -> &'static str {
     match (piece, color) {
         (Piece::King, Color::White) => "♔",
@@ -104,7 +133,7 @@ mod tests {
                 promotion: promo,
             };
             let uci = format_move(mv);
-            let parsed = parse_uci_move(&uci).expect(&format!("failed to parse '{uci}'"));
+            let parsed = parse_uci_move(&uci).unwrap_or_else(|| panic!("failed to parse '{uci}'"));
             assert_eq!(parsed.from, mv.from, "from mismatch for '{uci}'");
             assert_eq!(parsed.to, mv.to, "to mismatch for '{uci}'");
             assert_eq!(parsed.promotion, mv.promotion, "promo mismatch for '{uci}'");
@@ -149,6 +178,17 @@ mod tests {
         assert_eq!(mv.to, Square::new(File::A, Rank::First));
     }
 
+    #[test]
+    fn parse_legal_accepts_both_castling_forms() {
+        let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+        let short = parse_uci_move("e1h1").unwrap();
+        assert_eq!(parse_legal_uci_move(&board, "e1h1"), Some(short));
+        assert_eq!(parse_legal_uci_move(&board, "e1g1"), Some(short));
+        assert_eq!(parse_legal_uci_move(&board, "e1c1"), parse_uci_move("e1a1"));
+        assert_eq!(parse_legal_uci_move(&board, "e1e3"), None);
+        assert_eq!(parse_legal_uci_move(&Board::default(), "e2e5"), None);
+    }
+
     #[test]
     fn piece_unicode_all_distinct() {
         let pieces = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
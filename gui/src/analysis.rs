@@ -0,0 +1,190 @@
+//! Analysis panel: NN and baseline root scores for every legal move, with arrows.
+
+use crate::analysis_scores::{analyze, format_cp, move_target, AnalysisResult, TOP_N};
+use eframe::egui;
+use engine::game::GameState;
+use engine::{format_san, NnEvalBot, Square};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const NN_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 130, 220);
+const BASELINE_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 140, 40);
+const DISAGREE_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 70, 70);
+/// Finished analyses kept, so stepping back and forth doesn't search again.
+const CACHE_SIZE: usize = 32;
+
+pub struct Analysis {
+    pub enabled: bool,
+    pub depth: u32,
+    /// Position and depth currently shown.
+    requested: Option<(String, u32)>,
+    /// Finished analyses, oldest first.
+    results: Arc<Mutex<Vec<AnalysisResult>>>,
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Analysis {
+            enabled: false,
+            depth: 3,
+            requested: None,
+            results: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl Analysis {
+    /// Start analysing `game` in the background unless it is already done or running.
+    pub fn update(&mut self, game: &GameState, nn: Option<Arc<NnEvalBot>>) {
+        if !self.enabled {
+            return;
+        }
+        let key = (game.board.to_string(), self.depth);
+        if self.requested.as_ref() == Some(&key) {
+            return;
+        }
+        let cached = {
+            let results = self.results.lock().unwrap();
+            results.iter().any(|r| r.fen == key.0 && r.depth == key.1)
+        };
+        self.requested = Some(key);
+        if cached {
+            return;
+        }
+        let game = game.clone();
+        let depth = self.depth;
+        let results = self.results.clone();
+        thread::spawn(move || {
+            let analysis = analyze(&game, depth, nn.as_deref());
+            let mut results = results.lock().unwrap();
+            results.push(analysis);
+            if results.len() > CACHE_SIZE {
+                results.remove(0);
+            }
+        });
+    }
+
+    /// Run `f` on the analysis of the position shown, once it is finished.
+    fn with_current<R>(&self, f: impl FnOnce(&AnalysisResult) -> R) -> Option<R> {
+        let (fen, depth) = self.requested.as_ref()?;
+        let results = self.results.lock().unwrap();
+        let current = results
+            .iter()
+            .find(|r| r.fen == *fen && r.depth == *depth)?;
+        Some(f(current))
+    }
+
+    /// Arrows for each engine's top moves; `center` maps a square to screen space.
+    pub fn draw_arrows(
+        &self,
+        painter: &egui::Painter,
+        cell_size: f32,
+        center: impl Fn(Square) -> egui::Pos2,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.with_current(|result| {
+            let nn = result.nn_moves().iter().map(|&(m, _)| (m, NN_COLOR));
+            let baseline = result.baseline.iter().map(|&(m, _)| (m, BASELINE_COLOR));
+            for (moves, offset) in [
+                (nn.take(TOP_N).collect::<Vec<_>>(), -0.08),
+                (baseline.take(TOP_N).collect::<Vec<_>>(), 0.08),
+            ] {
+                for (rank, (mv, color)) in moves.into_iter().enumerate() {
+                    // Best move thickest; the two engines' arrows sit side by side.
+                    let width = cell_size * (0.14 - 0.035 * rank as f32);
+                    let shift = egui::vec2(offset * cell_size, offset * cell_size);
+                    let from = center(mv.from) + shift;
+                    let to = center(move_target(&result.board, mv)) + shift;
+                    let alpha = 200 - 50 * rank as u8;
+                    let color = egui::Color32::from_rgba_unmultiplied(
+                        color.r(),
+                        color.g(),
+                        color.b(),
+                        alpha,
+                    );
+                    painter.arrow(from, to - from, egui::Stroke::new(width, color));
+                }
+            }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Analyse");
+            ui.add(egui::Slider::new(&mut self.depth, 1..=6).text("depth"));
+        });
+        if !self.enabled {
+            return;
+        }
+        let shown = self.with_current(|result| {
+            let Some(&(baseline_best, _)) = result.baseline.first() else {
+                ui.label("No legal moves.");
+                return;
+            };
+            match &result.nn {
+                None => {
+                    ui.small("Load a model to compare with the NN.");
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(DISAGREE_COLOR, format!("NN failed: {e}"));
+                }
+                Some(Ok(scores)) => {
+                    let nn_best = scores[0].0;
+                    if nn_best == baseline_best {
+                        ui.label(format!(
+                            "Both prefer {}",
+                            format_san(&result.board, nn_best)
+                        ));
+                    } else {
+                        ui.colored_label(
+                            DISAGREE_COLOR,
+                            format!(
+                                "NN prefers {}, baseline {}",
+                                format_san(&result.board, nn_best),
+                                format_san(&result.board, baseline_best)
+                            ),
+                        );
+                    }
+                }
+            }
+
+            egui::ScrollArea::vertical()
+                .id_salt("analysis_moves")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("analysis_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Move");
+                            ui.colored_label(NN_COLOR, "NN");
+                            ui.colored_label(BASELINE_COLOR, format!("Depth {}", result.depth));
+                            ui.end_row();
+                            // Listed in the baseline's order, ranks in brackets.
+                            for (rank, &(mv, cp)) in result.baseline.iter().enumerate() {
+                                let san = format_san(&result.board, mv);
+                                if result.disagrees(mv) {
+                                    ui.colored_label(DISAGREE_COLOR, san);
+                                } else {
+                                    ui.label(san);
+                                }
+                                let nn_score =
+                                    result.nn_rank(mv).map(|r| (r, result.nn_moves()[r].1));
+                                match nn_score {
+                                    Some((r, score)) => {
+                                        ui.label(format!("{score:+.3} ({})", r + 1))
+                                    }
+                                    None => ui.label("-"),
+                                };
+                                ui.label(format!("{} ({})", format_cp(cp), rank + 1));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+        if shown.is_none() {
+            ui.label("Analysing...");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Root scores behind the analysis panel, and where the two engines disagree.
+
+use engine::analysis::is_mate_score;
+use engine::diagram::move_squares;
+use engine::game::GameState;
+use engine::search::best_move_with_scores_enhanced;
+use engine::{Board, Move, NnEvalBot, SearchContext, Square};
+
+/// Moves marked with arrows per engine.
+pub const TOP_N: usize = 3;
+
+/// Root scores of one position, best first, from the mover's point of view.
+pub struct AnalysisResult {
+    pub fen: String,
+    pub depth: u32,
+    pub board: Board,
+    /// Network output units, `None` without a model.
+    pub nn: Option<Result<Vec<(Move, f32)>, String>>,
+    /// Centipawns from `best_move_with_scores_enhanced`.
+    pub baseline: Vec<(Move, i32)>,
+}
+
+impl AnalysisResult {
+    pub fn nn_moves(&self) -> &[(Move, f32)] {
+        match &self.nn {
+            Some(Ok(scores)) => scores,
+            _ => &[],
+        }
+    }
+
+    pub fn nn_rank(&self, mv: Move) -> Option<usize> {
+        self.nn_moves().iter().position(|&(m, _)| m == mv)
+    }
+
+    pub fn baseline_rank(&self, mv: Move) -> Option<usize> {
+        self.baseline.iter().position(|&(m, _)| m == mv)
+    }
+
+    /// A move in one engine's top N but not the other's. Without NN scores
+    /// there is nothing to disagree with.
+    pub fn disagrees(&self, mv: Move) -> bool {
+        if !matches!(self.nn, Some(Ok(_))) {
+            return false;
+        }
+        let nn_top = self.nn_rank(mv).is_some_and(|r| r < TOP_N);
+        let baseline_top = self.baseline_rank(mv).is_some_and(|r| r < TOP_N);
+        nn_top != baseline_top
+    }
+}
+
+pub fn analyze(game: &GameState, depth: u32, nn: Option<&NnEvalBot>) -> AnalysisResult {
+    let nn = nn.map(|nn| nn.score_moves(game).map_err(|e| e.to_string()));
+    let mut ctx = SearchContext::new();
+    let mut baseline = if game.legal_moves().is_empty() {
+        Vec::new()
+    } else {
+        best_move_with_scores_enhanced(&mut ctx, &game.board, depth)
+    };
+    baseline.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    AnalysisResult {
+        fen: game.board.to_string(),
+        depth,
+        board: game.board.clone(),
+        nn,
+        baseline,
+    }
+}
+
+pub fn format_cp(score: i32) -> String {
+    if is_mate_score(score) {
+        if score > 0 { "mate" } else { "-mate" }.to_string()
+    } else {
+        format!("{:+.2}", score as f32 / 100.0)
+    }
+}
+
+/// Where `mv` lands on the board: castling, stored as king-takes-rook, goes to
+/// the king's destination instead.
+pub fn move_target(board: &Board, mv: Move) -> Square {
+    move_squares(board, mv)[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::search::MATE_SCORE;
+    use engine::uci::parse_uci_move;
+
+    fn mv(uci: &str) -> Move {
+        parse_uci_move(uci).unwrap()
+    }
+
+    fn result(nn: Option<Result<Vec<(Move, f32)>, String>>) -> AnalysisResult {
+        let baseline = ["e2e4", "d2d4", "g1f3", "c2c4"];
+        AnalysisResult {
+            fen: String::new(),
+            depth: 1,
+            board: Board::default(),
+            nn,
+            baseline: baseline.iter().map(|&m| (mv(m), 0)).collect(),
+        }
+    }
+
+    #[test]
+    fn disagreement_is_a_move_in_only_one_top_n() {
+        let nn = ["e2e4", "c2c4", "d2d4", "g1f3"];
+        let result = result(Some(Ok(nn.iter().map(|&m| (mv(m), 0.0)).collect())));
+        assert_eq!(result.nn_rank(mv("c2c4")), Some(1));
+        assert_eq!(result.baseline_rank(mv("c2c4")), Some(3));
+        assert!(!result.disagrees(mv("e2e4")));
+        assert!(!result.disagrees(mv("d2d4")));
+        assert!(result.disagrees(mv("g1f3")));
+        assert!(result.disagrees(mv("c2c4")));
+        assert!(!result.disagrees(mv("a2a3")));
+    }
+
+    #[test]
+    fn no_disagreement_without_nn_scores() {
+        for nn in [None, Some(Err("no session".to_string()))] {
+            let result = result(nn);
+            assert!(result.nn_moves().is_empty());
+            assert!(!result.disagrees(mv("e2e4")));
+        }
+    }
+
+    #[test]
+    fn analysis_sorts_baseline_moves_best_first() {
+        let game = GameState::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let result = analyze(&game, 2, None);
+        assert_eq!(result.baseline[0].0, mv("a1a8"));
+        assert!(result.baseline.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(result.nn.is_none());
+
+        let mated = GameState::from_fen("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 1 1").unwrap();
+        assert!(analyze(&mated, 2, None).baseline.is_empty());
+    }
+
+    #[test]
+    fn formats_centipawns_and_mates() {
+        assert_eq!(format_cp(150), "+1.50");
+        assert_eq!(format_cp(-5), "-0.05");
+        assert_eq!(format_cp(MATE_SCORE - 3), "mate");
+        assert_eq!(format_cp(3 - MATE_SCORE), "-mate");
+    }
+}
@@ -0,0 +1,252 @@
+//! Chess clocks for time-controlled games.
+
+use eframe::egui;
+use engine::{Board, Color, TimeLeft};
+use std::time::{Duration, Instant};
+
+const LOW_TIME: Duration = Duration::from_secs(10);
+
+/// Both sides' clocks. Only the side to move's clock runs, and only between
+/// `start` and `stop`, so pauses and bot-vs-bot move delays cost nothing.
+#[derive(Clone)]
+pub struct Clock {
+    pub increment: Duration,
+    remaining: [Duration; 2],
+    running_since: Option<Instant>,
+    /// Time charged to the move in progress so far.
+    spent: Duration,
+    /// Clock times after each ply, the start of the game first, for takebacks.
+    history: Vec<[Duration; 2]>,
+    /// Time taken by each move of the game.
+    pub think_times: Vec<Duration>,
+    /// The side that ran out of time.
+    pub flagged: Option<Color>,
+}
+
+impl Clock {
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        Clock {
+            increment,
+            remaining: [base; 2],
+            running_since: None,
+            spent: Duration::ZERO,
+            history: vec![[base; 2]],
+            think_times: Vec::new(),
+            flagged: None,
+        }
+    }
+
+    /// Time left for `side` when `to_move` is to move.
+    pub fn remaining(&self, side: Color, to_move: Color) -> Duration {
+        let running = match self.running_since {
+            Some(since) if side == to_move => since.elapsed(),
+            _ => Duration::ZERO,
+        };
+        self.remaining[side as usize].saturating_sub(running)
+    }
+
+    /// What a bot to move is told about its clock.
+    pub fn time_left(&self, to_move: Color) -> TimeLeft {
+        TimeLeft {
+            remaining: self.remaining(to_move, to_move),
+            increment: self.increment,
+        }
+    }
+
+    /// Start the side to move's clock, if it isn't running already.
+    pub fn start(&mut self) {
+        if self.running_since.is_none() && self.flagged.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    /// Stop the clock, charging the time since `start` to `to_move`.
+    pub fn stop(&mut self, to_move: Color) {
+        if let Some(since) = self.running_since.take() {
+            let elapsed = since.elapsed();
+            let left = &mut self.remaining[to_move as usize];
+            *left = left.saturating_sub(elapsed);
+            self.spent += elapsed;
+        }
+    }
+
+    /// `to_move` has moved: stop their clock, record the think time and add
+    /// the increment. The opponent's clock starts with the next `start`.
+    pub fn record_move(&mut self, to_move: Color) {
+        self.stop(to_move);
+        self.think_times.push(std::mem::take(&mut self.spent));
+        self.remaining[to_move as usize] += self.increment;
+        self.history.push(self.remaining);
+    }
+
+    /// Flag `to_move` if their time is up. Returns true once a side has flagged.
+    pub fn check_flag(&mut self, to_move: Color) -> bool {
+        if self.flagged.is_none() && self.remaining(to_move, to_move).is_zero() {
+            self.stop(to_move);
+            self.flagged = Some(to_move);
+        }
+        self.flagged.is_some()
+    }
+
+    /// Restore the clocks to how they stood after `ply` plies.
+    pub fn take_back(&mut self, ply: usize) {
+        let ply = ply.min(self.history.len() - 1);
+        self.history.truncate(ply + 1);
+        self.think_times.truncate(ply);
+        self.remaining = self.history[ply];
+        self.running_since = None;
+        self.spent = Duration::ZERO;
+        self.flagged = None;
+    }
+}
+
+/// Result text when `flagged` ran out of time: a loss, unless the opponent has
+/// nothing left but the king to mate with.
+pub fn flag_result(board: &Board, flagged: Color, names: &[String; 2]) -> String {
+    let winner = !flagged;
+    if board.colors(winner).len() == 1 {
+        format!(
+            "Draw: {} ran out of time but {} has only a king",
+            names[flagged as usize], names[winner as usize]
+        )
+    } else {
+        format!("{} wins on time!", names[winner as usize])
+    }
+}
+
+/// "4:59", or "0:09.4" under 20 seconds.
+pub fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs < 20 {
+        format!(
+            "{}:{:02}.{}",
+            secs / 60,
+            secs % 60,
+            time.subsec_millis() / 100
+        )
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+pub fn format_think_time(time: Duration) -> String {
+    format!("{:.2}s", time.as_secs_f64())
+}
+
+/// Both clocks, Black above White, the running one highlighted.
+pub fn clock_ui(ui: &mut egui::Ui, clock: &Clock, names: &[String; 2], to_move: Color) {
+    for side in [Color::Black, Color::White] {
+        let time = clock.remaining(side, to_move);
+        let active = side == to_move && clock.flagged.is_none();
+        let mut text = egui::RichText::new(format_clock(time))
+            .monospace()
+            .size(22.0);
+        if clock.flagged == Some(side) || (active && time < LOW_TIME) {
+            text = text.color(egui::Color32::from_rgb(220, 60, 60));
+        } else if active {
+            text = text.strong();
+        }
+        ui.horizontal(|ui| {
+            let frame = egui::Frame::none()
+                .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+                .rounding(4.0);
+            let frame = if active {
+                frame.fill(ui.visuals().selection.bg_fill)
+            } else {
+                frame.fill(ui.visuals().faint_bg_color)
+            };
+            frame.show(ui, |ui| ui.label(text));
+            ui.label(&names[side as usize]);
+        });
+    }
+    if let Some(&last) = clock.think_times.last() {
+        ui.small(format!("Last move took {}", format_think_time(last)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn moves_add_the_increment_to_the_mover() {
+        let mut clock = Clock::new(60 * SEC, 2 * SEC);
+        clock.record_move(Color::White);
+        clock.record_move(Color::Black);
+        clock.record_move(Color::White);
+        assert_eq!(clock.remaining(Color::White, Color::Black), 64 * SEC);
+        assert_eq!(clock.remaining(Color::Black, Color::White), 62 * SEC);
+        assert_eq!(clock.think_times.len(), 3);
+        let left = clock.time_left(Color::Black);
+        assert_eq!((left.remaining, left.increment), (62 * SEC, 2 * SEC));
+    }
+
+    #[test]
+    fn only_the_running_side_is_charged() {
+        let mut clock = Clock::new(60 * SEC, Duration::ZERO);
+        clock.start();
+        std::thread::sleep(Duration::from_millis(5));
+        clock.record_move(Color::White);
+        assert!(clock.remaining(Color::White, Color::Black) <= 60 * SEC - clock.think_times[0]);
+        assert!(clock.think_times[0] >= Duration::from_millis(5));
+        assert_eq!(clock.remaining(Color::Black, Color::Black), 60 * SEC);
+    }
+
+    #[test]
+    fn take_back_restores_the_clocks_after_a_ply() {
+        let mut clock = Clock::new(60 * SEC, 5 * SEC);
+        for side in [Color::White, Color::Black, Color::White] {
+            clock.record_move(side);
+        }
+        clock.flagged = Some(Color::Black);
+        clock.take_back(1);
+        assert_eq!(clock.remaining(Color::White, Color::Black), 65 * SEC);
+        assert_eq!(clock.remaining(Color::Black, Color::Black), 60 * SEC);
+        assert_eq!(clock.think_times.len(), 1);
+        assert_eq!(clock.flagged, None);
+
+        // Past the end keeps everything; the next move extends from the restored ply.
+        clock.take_back(9);
+        assert_eq!(clock.think_times.len(), 1);
+        clock.record_move(Color::Black);
+        clock.take_back(0);
+        assert_eq!(clock.remaining(Color::White, Color::White), 60 * SEC);
+        assert!(clock.think_times.is_empty());
+    }
+
+    #[test]
+    fn flags_once_time_runs_out() {
+        let mut clock = Clock::new(SEC, Duration::ZERO);
+        assert!(!clock.check_flag(Color::White));
+
+        let mut clock = Clock::new(Duration::ZERO, Duration::ZERO);
+        clock.start();
+        assert!(clock.check_flag(Color::Black));
+        assert_eq!(clock.flagged, Some(Color::Black));
+        // A flagged clock stays stopped.
+        clock.start();
+        assert!(clock.check_flag(Color::White));
+        assert_eq!(clock.flagged, Some(Color::Black));
+    }
+
+    #[test]
+    fn flagging_against_a_lone_king_is_a_draw() {
+        let names = ["Alice".to_string(), "Bot".to_string()];
+        let board: Board = "4k3/8/8/8/8/8/8/R3K3 b - - 0 1".parse().unwrap();
+        assert_eq!(
+            flag_result(&board, Color::Black, &names),
+            "Alice wins on time!"
+        );
+        let both: Board = "r3k3/8/8/8/8/8/8/R3K3 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            flag_result(&both, Color::White, &names),
+            "Bot wins on time!"
+        );
+        assert_eq!(
+            flag_result(&board, Color::White, &names),
+            "Draw: Alice ran out of time but Bot has only a king"
+        );
+    }
+}
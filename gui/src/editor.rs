@@ -0,0 +1,232 @@
+//! Board editor: set up a position by hand, with FEN import and export.
+
+use crate::position::{EditedPosition, Pieces};
+use eframe::egui;
+use engine::{piece_unicode, Board, Color, File, Piece, Square};
+
+const PIECES: [Piece; 6] = [
+    Piece::King,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
+/// What the editor asks the app to do.
+pub enum EditorAction {
+    /// Start a game from this FEN with the players picked in the setup panel.
+    Play(String),
+}
+
+pub struct Editor {
+    pub active: bool,
+    position: EditedPosition,
+    /// Palette piece placed by clicking a square.
+    palette: Option<(Piece, Color)>,
+    /// Piece being dragged, from the board or the palette.
+    dragging: Option<(Piece, Color)>,
+    /// FEN text field, for pasting.
+    fen_input: String,
+    error: Option<String>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            active: false,
+            position: EditedPosition::from_board(&Board::default()),
+            palette: None,
+            dragging: None,
+            fen_input: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl Editor {
+    pub fn pieces(&self) -> &Pieces {
+        &self.position.pieces
+    }
+
+    pub fn load_board(&mut self, board: &Board) {
+        self.position = EditedPosition::from_board(board);
+        self.error = None;
+    }
+
+    fn load_fen(&mut self, fen: &str) {
+        match fen.trim().parse::<Board>() {
+            Ok(board) => self.load_board(&board),
+            Err(e) => self.error = Some(format!("Invalid FEN: {e:?}")),
+        }
+    }
+
+    /// The edited position, if cozy-chess accepts it.
+    pub fn validate(&self) -> Result<Board, String> {
+        self.position.validate()
+    }
+
+    /// Mouse input on the board: drag pieces between squares or off the board to
+    /// remove them, click to place the palette piece, right-click to clear a square.
+    pub fn board_input(
+        &mut self,
+        ctx: &egui::Context,
+        square_at: impl Fn(egui::Pos2) -> Option<Square>,
+    ) {
+        let (pressed, released, secondary, pos) = ctx.input(|i| {
+            (
+                i.pointer.primary_pressed(),
+                i.pointer.primary_released(),
+                i.pointer.secondary_pressed(),
+                i.pointer.interact_pos(),
+            )
+        });
+        let Some(pos) = pos else {
+            return;
+        };
+        let square = square_at(pos);
+
+        if pressed {
+            if let Some(sq) = square {
+                let slot = &mut self.position.pieces[sq as usize];
+                match self.palette {
+                    Some(piece) => *slot = Some(piece),
+                    None => self.dragging = slot.take(),
+                }
+            }
+        }
+        if secondary {
+            if let Some(sq) = square {
+                self.position.pieces[sq as usize] = None;
+            }
+        }
+        if released {
+            if let Some(piece) = self.dragging.take() {
+                if let Some(sq) = square {
+                    self.position.pieces[sq as usize] = Some(piece);
+                }
+            }
+        }
+    }
+
+    /// The dragged piece, drawn under the pointer above everything else.
+    pub fn draw_drag(&self, ctx: &egui::Context, size: f32) {
+        let (Some((piece, color)), Some(pos)) = (self.dragging, ctx.pointer_interact_pos()) else {
+            return;
+        };
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Tooltip,
+            egui::Id::new("editor_drag"),
+        ));
+        painter.text(
+            pos,
+            egui::Align2::CENTER_CENTER,
+            piece_unicode(piece, color),
+            egui::FontId::proportional(size),
+            egui::Color32::BLACK,
+        );
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<EditorAction> {
+        let mut action = None;
+        ui.small("Drag pieces; drop off the board to remove, right-click to clear.");
+        for color in [Color::White, Color::Black] {
+            ui.horizontal(|ui| {
+                for piece in PIECES {
+                    let selected = self.palette == Some((piece, color));
+                    let button = egui::SelectableLabel::new(
+                        selected,
+                        egui::RichText::new(piece_unicode(piece, color)).size(22.0),
+                    );
+                    let response = ui.add(button).interact(egui::Sense::click_and_drag());
+                    if response.clicked() {
+                        self.palette = if selected { None } else { Some((piece, color)) };
+                    }
+                    if response.drag_started() {
+                        self.dragging = Some((piece, color));
+                    }
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut self.position.side_to_move,
+                Color::White,
+                "White to move",
+            );
+            ui.radio_value(&mut self.position.side_to_move, Color::Black, "Black");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Castling");
+            for (flag, label) in self.position.castling.iter_mut().zip(["K", "Q", "k", "q"]) {
+                ui.checkbox(flag, label);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("En passant");
+            let selected = match self.position.en_passant {
+                Some(file) => ((b'a' + file as u8) as char).to_string(),
+                None => "-".to_string(),
+            };
+            egui::ComboBox::from_id_salt("en_passant")
+                .selected_text(selected)
+                .width(40.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.position.en_passant, None, "-");
+                    for f in 0..8 {
+                        let file = File::index(f);
+                        let label = ((b'a' + f as u8) as char).to_string();
+                        ui.selectable_value(&mut self.position.en_passant, Some(file), label);
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Clear").clicked() {
+                self.position.clear();
+            }
+            if ui.button("Start position").clicked() {
+                self.load_board(&Board::default());
+            }
+        });
+
+        ui.add_space(4.0);
+        let fen = self.position.to_fen();
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.fen_input).hint_text("paste a FEN"));
+            if ui.button("Load").clicked() {
+                let input = self.fen_input.clone();
+                self.load_fen(&input);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Copy FEN").clicked() {
+                ui.ctx().copy_text(fen.clone());
+            }
+            ui.small(&fen);
+        });
+
+        match self.position.validate() {
+            Ok(_) => {
+                ui.horizontal(|ui| {
+                    if ui.button("Play from here").clicked() {
+                        action = Some(EditorAction::Play(fen.clone()));
+                    }
+                    if ui.button("Close").clicked() {
+                        self.active = false;
+                    }
+                });
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e);
+                if ui.button("Close").clicked() {
+                    self.active = false;
+                }
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        action
+    }
+}
@@ -0,0 +1,169 @@
+//! Evaluation bar and per-ply eval graph.
+
+use eframe::egui;
+use engine::eval::evaluate;
+use engine::game::GameState;
+use engine::{Color, NnEvalBot};
+
+/// Both engines' view of one position, from White's point of view.
+#[derive(Clone, Copy)]
+pub struct PlyEval {
+    /// `eval::evaluate`, in centipawns.
+    pub static_cp: i32,
+    /// Raw network output, when a model is loaded.
+    pub nn: Option<f32>,
+}
+
+pub fn eval_position(game: &GameState, nn: Option<&NnEvalBot>) -> PlyEval {
+    let sign = if game.side_to_move() == Color::White {
+        1
+    } else {
+        -1
+    };
+    PlyEval {
+        static_cp: sign * evaluate(&game.board),
+        nn: nn
+            .and_then(|nn| nn.nn_eval(game).ok())
+            .map(|v| sign as f32 * v),
+    }
+}
+
+const STATIC_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 140, 40);
+const NN_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 130, 220);
+
+/// Centipawns squashed into -1..1.
+fn squash_cp(cp: i32) -> f32 {
+    (cp as f32 / 400.0).tanh()
+}
+
+/// The network's output has no fixed units, so it is scaled by its largest
+/// magnitude over the game before squashing.
+fn nn_scale(evals: &[PlyEval]) -> f32 {
+    let max = evals
+        .iter()
+        .filter_map(|e| e.nn)
+        .fold(0.0f32, |m, v| m.max(v.abs()));
+    if max > 1e-6 {
+        max
+    } else {
+        1.0
+    }
+}
+
+/// Vertical bar, White's share at the bottom. Driven by the NN when it has
+/// evaluated the position, by the static eval otherwise.
+pub fn eval_bar(painter: &egui::Painter, rect: egui::Rect, evals: &[PlyEval], ply: usize) {
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(40));
+    let Some(eval) = evals.get(ply) else {
+        return;
+    };
+    let (value, label) = match eval.nn {
+        Some(v) => ((v / nn_scale(evals)).tanh(), format!("{v:+.2}")),
+        None => (
+            squash_cp(eval.static_cp),
+            format!("{:+.1}", eval.static_cp as f32 / 100.0),
+        ),
+    };
+    let white_height = rect.height() * (0.5 + 0.5 * value);
+    let white =
+        egui::Rect::from_min_max(egui::pos2(rect.min.x, rect.max.y - white_height), rect.max);
+    painter.rect_filled(white, 0.0, egui::Color32::from_gray(235));
+    painter.text(
+        rect.center_top() + egui::vec2(0.0, 4.0),
+        egui::Align2::CENTER_TOP,
+        label,
+        egui::FontId::proportional(10.0),
+        if value > 0.9 {
+            egui::Color32::BLACK
+        } else {
+            egui::Color32::WHITE
+        },
+    );
+}
+
+/// Static (orange) and NN (blue) eval over the game, with `current` marked.
+/// Returns the ply that was clicked, if any.
+pub fn eval_graph(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    evals: &[PlyEval],
+    current: usize,
+) -> Option<usize> {
+    let response = ui.interact(rect, ui.id().with("eval_graph"), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(30));
+    painter.hline(
+        rect.x_range(),
+        rect.center().y,
+        egui::Stroke::new(1.0, egui::Color32::from_gray(80)),
+    );
+    if evals.is_empty() {
+        return None;
+    }
+
+    let last = (evals.len() - 1).max(1) as f32;
+    let x_of = |ply: usize| rect.min.x + rect.width() * ply as f32 / last;
+    let y_of = |v: f32| rect.center().y - v * rect.height() * 0.45;
+    let ply_at = |x: f32| {
+        (((x - rect.min.x) / rect.width() * last).round().max(0.0) as usize).min(evals.len() - 1)
+    };
+
+    let scale = nn_scale(evals);
+    let static_points: Vec<egui::Pos2> = evals
+        .iter()
+        .enumerate()
+        .map(|(i, e)| egui::pos2(x_of(i), y_of(squash_cp(e.static_cp))))
+        .collect();
+    painter.add(egui::Shape::line(
+        static_points,
+        egui::Stroke::new(1.5, STATIC_COLOR),
+    ));
+    let nn_points: Vec<egui::Pos2> = evals
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| e.nn.map(|v| egui::pos2(x_of(i), y_of((v / scale).tanh()))))
+        .collect();
+    if nn_points.len() > 1 {
+        painter.add(egui::Shape::line(
+            nn_points,
+            egui::Stroke::new(1.5, NN_COLOR),
+        ));
+    }
+
+    painter.vline(
+        x_of(current.min(evals.len() - 1)),
+        rect.y_range(),
+        egui::Stroke::new(1.0, egui::Color32::from_gray(200)),
+    );
+    painter.text(
+        rect.min + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        "static",
+        egui::FontId::proportional(10.0),
+        STATIC_COLOR,
+    );
+    painter.text(
+        rect.min + egui::vec2(40.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        "NN",
+        egui::FontId::proportional(10.0),
+        NN_COLOR,
+    );
+
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        if let Some(pos) = ui.ctx().pointer_hover_pos() {
+            let ply = ply_at(pos.x);
+            let e = evals[ply];
+            ui.label(format!("ply {ply}"));
+            ui.label(format!("static {:+.2}", e.static_cp as f32 / 100.0));
+            if let Some(v) = e.nn {
+                ui.label(format!("NN {v:+.3}"));
+            }
+        }
+    });
+    if response.clicked() {
+        response.interact_pointer_pos().map(|pos| ply_at(pos.x))
+    } else {
+        None
+    }
+}
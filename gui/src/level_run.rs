@@ -0,0 +1,367 @@
+//! Run a whole `compete` level in the background with a live scoreboard.
+
+use crate::replay_games::ReplayGame;
+use eframe::egui;
+use engine::compete::{
+    pass_points, run_game, score_outcome, select_positions, GameResult, GameScore, LevelTally,
+};
+use engine::game::GameState;
+use engine::openings::OpeningEntry;
+use engine::uci::parse_uci_move;
+use engine::{BaselineBot, Board, Color, Level, Move, NnEvalBot, ALL_LEVELS};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const WIN_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 170, 90);
+const LOSS_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 70, 70);
+
+/// One game of the level, in schedule order.
+struct FinishedGame {
+    opening: String,
+    nn_color: Color,
+    points: f64,
+    result: GameResult,
+}
+
+impl FinishedGame {
+    fn outcome(&self) -> &'static str {
+        match GameScore::from_points(self.points) {
+            GameScore::Win => "win",
+            GameScore::Draw => "draw",
+            GameScore::Loss => "loss",
+        }
+    }
+
+    fn to_replay(&self, level: Level) -> ReplayGame {
+        let start = self.result.starting_fen.parse().unwrap_or_default();
+        let mut boards: Vec<Board> = vec![start];
+        let mut moves = Vec::new();
+        let mut sans = Vec::new();
+        for record in &self.result.move_history {
+            let (Some(mv), Ok(board)) = (parse_uci_move(&record.uci), record.fen.parse()) else {
+                break;
+            };
+            moves.push(mv);
+            boards.push(board);
+            sans.push(record.san.clone());
+        }
+        ReplayGame {
+            level: Some(level.value()),
+            opening: self.opening.clone(),
+            nn_side: Some(self.nn_color),
+            outcome: self.outcome().to_string(),
+            boards,
+            moves,
+            sans,
+        }
+    }
+}
+
+/// Game being played, as last reported by the runner.
+struct LiveGame {
+    game: GameState,
+    opening: String,
+    nn_color: Color,
+}
+
+/// Shared between the runner thread and the panel.
+struct Progress {
+    total_games: usize,
+    tally: LevelTally,
+    games: Vec<FinishedGame>,
+    current: Option<LiveGame>,
+    done: bool,
+    /// Why the run could not start.
+    error: Option<String>,
+}
+
+struct Run {
+    level: Level,
+    progress: Arc<Mutex<Progress>>,
+    stop: Arc<AtomicBool>,
+}
+
+/// The same schedule as `compete`: every opening with the NN as White, then as
+/// Black, each against a freshly reset baseline. The run loads its own copy of
+/// the model so its statistics don't mix with the rest of the window's.
+fn run_level(
+    level: Level,
+    model: PathBuf,
+    repetition_aware: bool,
+    positions: Vec<OpeningEntry>,
+    progress: Arc<Mutex<Progress>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut nn = match NnEvalBot::load(&model) {
+        Ok(nn) => nn,
+        Err(e) => {
+            let mut progress = progress.lock().unwrap();
+            progress.error = Some(format!("Failed to load model: {e}"));
+            progress.done = true;
+            return;
+        }
+    };
+    nn.repetition_aware = repetition_aware;
+    let mut baseline = BaselineBot::from_level(level);
+    baseline.repetition_aware = repetition_aware;
+    'schedule: for (i, opening) in positions.iter().enumerate() {
+        let label = match opening.label() {
+            Some(label) => format!("#{} {label}", i + 1),
+            None => format!("#{}", i + 1),
+        };
+        for nn_color in [Color::White, Color::Black] {
+            if stop.load(Ordering::Relaxed) {
+                break 'schedule;
+            }
+            baseline.reset();
+            progress.lock().unwrap().current = Some(LiveGame {
+                game: GameState::from_fen(&opening.fen).unwrap_or_default(),
+                opening: label.clone(),
+                nn_color,
+            });
+            let nn_is_white = nn_color == Color::White;
+            let result = run_game(
+                &nn,
+                &baseline,
+                Some(&opening.fen),
+                nn_is_white,
+                |game, _| {
+                    if let Some(live) = progress.lock().unwrap().current.as_mut() {
+                        live.game = game.clone();
+                    }
+                    !stop.load(Ordering::Relaxed)
+                },
+            );
+            if result.reason == "aborted" {
+                break 'schedule;
+            }
+            let points = score_outcome(&result.outcome, nn_color);
+            let mut progress = progress.lock().unwrap();
+            progress.tally.record(points);
+            progress.games.push(FinishedGame {
+                opening: label.clone(),
+                nn_color,
+                points,
+                result,
+            });
+        }
+    }
+    let mut progress = progress.lock().unwrap();
+    progress.current = None;
+    progress.done = true;
+}
+
+/// The "Compete" panel: pick a level, run it, follow the score.
+pub struct LevelRun {
+    pub level: Level,
+    /// Show the game in progress on the board.
+    pub watch: bool,
+    /// Score repetitions as draws in both bots' searches, like
+    /// `compete --repetition-aware`.
+    pub repetition_aware: bool,
+    run: Option<Run>,
+}
+
+impl Default for LevelRun {
+    fn default() -> Self {
+        LevelRun {
+            level: ALL_LEVELS[0],
+            watch: true,
+            repetition_aware: false,
+            run: None,
+        }
+    }
+}
+
+impl LevelRun {
+    fn start(&mut self, model: PathBuf, openings: &[OpeningEntry]) {
+        self.stop();
+        let positions = select_positions(openings);
+        let progress = Arc::new(Mutex::new(Progress {
+            total_games: positions.len() * 2,
+            tally: LevelTally::default(),
+            games: Vec::new(),
+            current: None,
+            done: false,
+            error: None,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (level, repetition_aware) = (self.level, self.repetition_aware);
+        let (thread_progress, thread_stop) = (progress.clone(), stop.clone());
+        thread::spawn(move || {
+            run_level(
+                level,
+                model,
+                repetition_aware,
+                positions,
+                thread_progress,
+                thread_stop,
+            )
+        });
+        self.run = Some(Run {
+            level,
+            progress,
+            stop,
+        });
+    }
+
+    fn stop(&mut self) {
+        if let Some(run) = &self.run {
+            run.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The game in progress and its last move, while watching.
+    pub fn position(&self) -> Option<(Board, Option<Move>)> {
+        let run = self.run.as_ref().filter(|_| self.watch)?;
+        let progress = run.progress.lock().unwrap();
+        let live = progress.current.as_ref()?;
+        Some((live.game.board.clone(), live.game.history.last().copied()))
+    }
+
+    /// Finished games of the last run, for the replay panel.
+    pub fn replay_games(&self) -> Vec<ReplayGame> {
+        let Some(run) = &self.run else {
+            return Vec::new();
+        };
+        let progress = run.progress.lock().unwrap();
+        progress
+            .games
+            .iter()
+            .map(|g| g.to_replay(run.level))
+            .collect()
+    }
+
+    /// `model` is the file of the loaded model, if any. Returns the index of a
+    /// finished game clicked in the results table.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        model: Option<&Path>,
+        openings: &[OpeningEntry],
+    ) -> Option<usize> {
+        let running = self
+            .run
+            .as_ref()
+            .is_some_and(|run| !run.progress.lock().unwrap().done);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("compete_level")
+                .selected_text(format!("L{} ({})", self.level.value(), self.level.name()))
+                .show_ui(ui, |ui| {
+                    for level in ALL_LEVELS {
+                        let label = format!("L{} ({})", level.value(), level.name());
+                        ui.selectable_value(&mut self.level, level, label);
+                    }
+                });
+            if running {
+                if ui.button("Stop").clicked() {
+                    self.stop();
+                }
+            } else {
+                let label = format!("Run level {}", self.level.value());
+                let button = ui.add_enabled(model.is_some(), egui::Button::new(label));
+                if let (true, Some(model)) = (button.clicked(), model) {
+                    self.start(model.to_path_buf(), openings);
+                }
+            }
+        });
+        ui.add_enabled(
+            !running,
+            egui::Checkbox::new(&mut self.repetition_aware, "Repetition-aware"),
+        )
+        .on_hover_text("Both bots score repeated positions as draws");
+        if model.is_none() {
+            ui.small("Load a model to run a level.");
+        }
+        let run = self.run.as_ref()?;
+        ui.checkbox(&mut self.watch, "Show the current game");
+
+        let progress = run.progress.lock().unwrap();
+        if let Some(error) = &progress.error {
+            ui.colored_label(LOSS_COLOR, error);
+            return None;
+        }
+        let tally = progress.tally;
+        let total = progress.total_games;
+        let needed = pass_points(total);
+        if let Some(live) = &progress.current {
+            let side = if live.nn_color == Color::White {
+                "White"
+            } else {
+                "Black"
+            };
+            ui.label(format!(
+                "Game {}/{total} · NN {side} · {}",
+                tally.games() + 1,
+                live.opening
+            ));
+        }
+        ui.label(format!(
+            "W {}  D {}  L {}   {:.1}/{} (need {needed})",
+            tally.wins,
+            tally.draws,
+            tally.losses,
+            tally.score,
+            tally.games()
+        ));
+        ui.add(
+            egui::ProgressBar::new((tally.score / needed.max(1) as f64).min(1.0) as f32)
+                .text(format!("{:.1} / {needed} points to pass", tally.score)),
+        );
+        let remaining = (total - tally.games()) as f64;
+        let verdict = if tally.score >= needed as f64 {
+            Some((WIN_COLOR, format!("Level {} passed", run.level.value())))
+        } else if tally.score + remaining < needed as f64 {
+            Some((LOSS_COLOR, format!("Level {} failed", run.level.value())))
+        } else if progress.done {
+            Some((LOSS_COLOR, "Stopped".to_string()))
+        } else {
+            None
+        };
+        if let Some((color, text)) = verdict {
+            ui.colored_label(color, text);
+        }
+
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("compete_results")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("compete_results_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("#");
+                        ui.strong("Opening");
+                        ui.strong("NN");
+                        ui.strong("Result");
+                        ui.strong("Plies");
+                        ui.end_row();
+                        for (i, game) in progress.games.iter().enumerate() {
+                            if ui.selectable_label(false, (i + 1).to_string()).clicked() {
+                                clicked = Some(i);
+                            }
+                            ui.label(&game.opening);
+                            ui.label(if game.nn_color == Color::White {
+                                "W"
+                            } else {
+                                "B"
+                            });
+                            let result = format!("{} ({})", game.outcome(), game.result.reason);
+                            match GameScore::from_points(game.points) {
+                                GameScore::Win => ui.colored_label(WIN_COLOR, result),
+                                GameScore::Draw => ui.label(result),
+                                GameScore::Loss => ui.colored_label(LOSS_COLOR, result),
+                            };
+                            ui.label(game.result.plies.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+        if !progress.games.is_empty() {
+            ui.small("Click a game number to replay it.");
+        }
+        clicked
+    }
+}
@@ -1,156 +1,886 @@
+mod analysis;
+mod analysis_scores;
+mod clock;
+mod editor;
+mod eval_view;
+mod level_run;
+mod nn_view;
+mod position;
+mod replay;
+mod replay_games;
+
 use eframe::egui;
-use engine::bot::{Bot, BaselineBot};
+use engine::bot::{BaselineBot, Bot};
 use engine::game::{GameState, Outcome};
-use engine::{piece_unicode, format_move, Color, File, Move, NnEvalBot, Piece, Rank, Square};
-use std::path::PathBuf;
+use engine::openings::{load_opening_entries, OpeningEntry};
+use engine::{
+    format_move, format_san, piece_unicode, Color, File, Level, Move, NnEvalBot, Piece, Rank,
+    Square, ALL_LEVELS,
+};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+use analysis::Analysis;
+use analysis_scores::move_target;
+use clock::{clock_ui, flag_result, format_think_time, Clock};
+use editor::{Editor, EditorAction};
+use eval_view::{eval_bar, eval_graph, eval_position, PlyEval};
+use level_run::LevelRun;
+use nn_view::NnView;
+use position::board_pieces;
+use replay::Replay;
+
+/// Who plays one side of a game.
+#[derive(Clone, Copy, PartialEq)]
+enum PlayerKind {
+    Human,
+    Baseline(Level),
+    Nn,
+}
+
+impl PlayerKind {
+    fn label(self) -> String {
+        match self {
+            PlayerKind::Human => "Human".to_string(),
+            PlayerKind::Baseline(level) => {
+                format!("BaselineBot L{} ({})", level.value(), level.name())
+            }
+            PlayerKind::Nn => "NnEvalBot".to_string(),
+        }
+    }
+}
+
+/// A player as run by the game thread.
+enum Player {
+    Human,
+    Baseline(BaselineBot),
+    Nn(Arc<NnEvalBot>),
+}
+
+impl Player {
+    fn bot(&self) -> Option<&dyn Bot> {
+        match self {
+            Player::Human => None,
+            Player::Baseline(bot) => Some(bot),
+            Player::Nn(nn) => Some(nn.as_ref()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StartPosition {
+    Standard,
+    Opening,
+    Fen,
+}
+
+/// The "New game" panel: players, model and start position for the next game.
+struct Setup {
+    white: PlayerKind,
+    black: PlayerKind,
+    model_path: String,
+    model: Option<Arc<NnEvalBot>>,
+    /// File `model` was loaded from.
+    model_file: Option<PathBuf>,
+    model_status: String,
+    openings: Vec<OpeningEntry>,
+    start: StartPosition,
+    opening_index: usize,
+    /// The current opening is being replayed with colours swapped.
+    reversed: bool,
+    fen: String,
+    /// Time control: minutes per side plus seconds added after every move.
+    timed: bool,
+    base_minutes: f64,
+    increment_secs: f64,
+    error: Option<String>,
+}
+
+impl Setup {
+    fn load_model(&mut self) {
+        let path = PathBuf::from(self.model_path.trim());
+        match NnEvalBot::load(&path) {
+            Ok(nn) => {
+                self.model_status = format!(
+                    "Loaded {} ({} parameters)",
+                    path.display(),
+                    nn.param_count
+                );
+                self.model = Some(Arc::new(nn));
+                self.model_file = Some(path);
+            }
+            Err(e) => {
+                self.model_status = format!("Failed to load model: {e}");
+                self.model = None;
+                self.model_file = None;
+            }
+        }
+    }
+
+    /// Ask for a model file, starting next to the current one if there is one.
+    fn pick_model(&self) -> Option<PathBuf> {
+        let mut dialog = rfd::FileDialog::new().add_filter("ONNX", &["onnx"]);
+        let current = PathBuf::from(self.model_path.trim());
+        if let Some(dir) = current.parent().filter(|dir| dir.is_dir()) {
+            dialog = dialog.set_directory(dir);
+        }
+        dialog.pick_file()
+    }
+
+    fn opening_label(&self, index: usize) -> String {
+        let entry = &self.openings[index];
+        entry.label().unwrap_or_else(|| entry.fen.clone())
+    }
+
+    fn starting_game(&self) -> Result<GameState, String> {
+        match self.start {
+            StartPosition::Standard => Ok(GameState::new()),
+            StartPosition::Opening => match self.openings.get(self.opening_index) {
+                Some(entry) => GameState::from_fen(&entry.fen),
+                None => Err("No opening selected".to_string()),
+            },
+            StartPosition::Fen => GameState::from_fen(self.fen.trim()),
+        }
+    }
+
+    /// Set up the game after this one the way `compete` schedules them: every
+    /// opening twice with colours swapped, then the next opening.
+    fn advance(&mut self) {
+        std::mem::swap(&mut self.white, &mut self.black);
+        if self.openings.is_empty() {
+            return;
+        }
+        if self.start == StartPosition::Opening && !self.reversed {
+            self.reversed = true;
+            return;
+        }
+        self.opening_index = if self.start == StartPosition::Opening {
+            (self.opening_index + 1) % self.openings.len()
+        } else {
+            0
+        };
+        self.start = StartPosition::Opening;
+        self.reversed = false;
+    }
+
+    fn clock(&self) -> Option<Clock> {
+        self.timed.then(|| {
+            Clock::new(
+                Duration::from_secs_f64(self.base_minutes * 60.0),
+                Duration::from_secs_f64(self.increment_secs),
+            )
+        })
+    }
+
+    fn player(&self, kind: PlayerKind) -> Result<Player, String> {
+        match kind {
+            PlayerKind::Human => Ok(Player::Human),
+            PlayerKind::Baseline(level) => Ok(Player::Baseline(BaselineBot::from_level(level))),
+            PlayerKind::Nn => match &self.model {
+                Some(nn) => Ok(Player::Nn(nn.clone())),
+                None => Err("Load a model before picking NnEvalBot".to_string()),
+            },
+        }
+    }
+}
+
 #[derive(Clone)]
 struct SharedState {
     game: GameState,
+    /// Bumped on every new game; a game thread stops once it no longer matches.
+    generation: u64,
+    /// Player names, White first.
+    names: [String; 2],
+    /// Evaluation of the position after each ply, starting with the initial one.
+    evals: Vec<PlyEval>,
     bot_thinking: bool,
     status_message: String,
+    /// Bots wait instead of moving; `step` lets one move through.
+    paused: bool,
+    step: bool,
+    /// Pause before each move of a bot-vs-bot game.
+    move_delay_ms: u64,
+    /// Clocks of a time-controlled game.
+    clock: Option<Clock>,
 }
 
 impl SharedState {
-    fn new() -> Self {
+    fn new(move_delay_ms: u64) -> Self {
         SharedState {
             game: GameState::new(),
+            generation: 0,
+            names: ["White".to_string(), "Black".to_string()],
+            evals: Vec::new(),
             bot_thinking: false,
             status_message: "White to move".to_string(),
+            paused: false,
+            step: false,
+            move_delay_ms,
+            clock: None,
         }
     }
 }
 
+/// Screen placement of the board squares, optionally with Black at the bottom.
+struct BoardGeometry {
+    rect: egui::Rect,
+    cell_size: f32,
+    flipped: bool,
+}
+
+impl BoardGeometry {
+    /// Square shown at column `col` and row `row`, counted from the top left.
+    fn square_at_cell(&self, col: usize, row: usize) -> Square {
+        let (file, rank) = if self.flipped {
+            (7 - col, row)
+        } else {
+            (col, 7 - row)
+        };
+        Square::new(File::index(file), Rank::index(rank))
+    }
+
+    fn square_rect(&self, sq: Square) -> egui::Rect {
+        let (file, rank) = (sq.file() as usize, sq.rank() as usize);
+        let (col, row) = if self.flipped {
+            (7 - file, rank)
+        } else {
+            (file, 7 - rank)
+        };
+        egui::Rect::from_min_size(
+            self.rect.min + egui::vec2(col as f32, row as f32) * self.cell_size,
+            egui::vec2(self.cell_size, self.cell_size),
+        )
+    }
+
+    fn square_at(&self, pos: egui::Pos2) -> Option<Square> {
+        if !self.rect.contains(pos) {
+            return None;
+        }
+        let rel = (pos - self.rect.min) / self.cell_size;
+        Some(self.square_at_cell((rel.x as usize).min(7), (rel.y as usize).min(7)))
+    }
+}
+
 struct ChessApp {
     shared: Arc<Mutex<SharedState>>,
-    move_sender: std::sync::mpsc::Sender<Move>,
+    move_sender: Sender<Move>,
     selected_square: Option<Square>,
-    legal_move_targets: Vec<Square>,
-    human_color: Color,
-    bot_vs_bot: bool,
+    /// Legal moves of the selected piece.
+    selected_moves: Vec<Move>,
+    /// The selected piece follows the pointer until the button is released.
+    dragging: bool,
+    /// Promotions awaiting the human's choice of piece.
+    pending_promotion: Option<Vec<Move>>,
+    /// Black at the bottom of the board.
+    flipped: bool,
+    /// Players of the game in progress, White first.
+    players: [PlayerKind; 2],
+    /// Earlier ply of the live game shown on the board instead of the current position.
+    view_ply: Option<usize>,
+    setup: Setup,
+    replay: Replay,
+    analysis: Analysis,
+    editor: Editor,
+    level_run: LevelRun,
+    nn_view: NnView,
 }
 
 impl ChessApp {
-    fn new(
-        shared: Arc<Mutex<SharedState>>,
-        move_sender: std::sync::mpsc::Sender<Move>,
-        bot_vs_bot: bool,
-    ) -> Self {
-        ChessApp {
-            shared,
+    fn new(setup: Setup, move_delay_ms: u64) -> Self {
+        let (move_sender, _) = std::sync::mpsc::channel();
+        let mut app = ChessApp {
+            shared: Arc::new(Mutex::new(SharedState::new(move_delay_ms))),
             move_sender,
             selected_square: None,
-            legal_move_targets: Vec::new(),
-            human_color: Color::White,
-            bot_vs_bot,
-        }
+            selected_moves: Vec::new(),
+            dragging: false,
+            pending_promotion: None,
+            flipped: false,
+            players: [setup.white, setup.black],
+            view_ply: None,
+            setup,
+            replay: Replay::default(),
+            analysis: Analysis::default(),
+            editor: Editor::default(),
+            level_run: LevelRun::default(),
+            nn_view: NnView::default(),
+        };
+        app.start_game();
+        app
+    }
+
+    /// Start a new game from the setup panel, replacing the one in progress.
+    fn start_game(&mut self) {
+        let started = self.setup.starting_game().and_then(|game| {
+            let white = self.setup.player(self.setup.white)?;
+            let black = self.setup.player(self.setup.black)?;
+            Ok((game, [white, black]))
+        });
+        let (game, players) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                self.setup.error = Some(e);
+                return;
+            }
+        };
+        self.setup.error = None;
+        self.players = [self.setup.white, self.setup.black];
+        self.clear_selection();
+        self.pending_promotion = None;
+        self.view_ply = None;
+        // Play from the bottom of the board when the human has Black.
+        self.flipped =
+            self.players[1] == PlayerKind::Human && self.players[0] != PlayerKind::Human;
+
+        let generation = {
+            let mut state = self.shared.lock().unwrap();
+            state.game = game;
+            state.generation += 1;
+            state.names = [self.setup.white.label(), self.setup.black.label()];
+            state.evals.clear();
+            state.bot_thinking = false;
+            state.status_message = String::new();
+            state.step = false;
+            state.clock = self.setup.clock();
+            state.generation
+        };
+
+        // Replacing the sender disconnects a previous game thread waiting on a human move.
+        let (tx, rx) = std::sync::mpsc::channel::<Move>();
+        self.move_sender = tx;
+        let shared = self.shared.clone();
+        let evaluator = self.setup.model.clone();
+        thread::spawn(move || run_game_loop(shared, generation, rx, players, evaluator));
     }
 
-    fn update_legal_targets(&mut self, from: Square) {
-        let state = self.shared.lock().unwrap();
-        self.legal_move_targets = state
-            .game
+    fn clear_selection(&mut self) {
+        self.selected_square = None;
+        self.selected_moves.clear();
+        self.dragging = false;
+    }
+
+    fn select(&mut self, game: &GameState, from: Square) {
+        self.selected_square = Some(from);
+        self.selected_moves = game
             .legal_moves()
             .into_iter()
             .filter(|mv| mv.from == from)
-            .map(|mv| mv.to)
             .collect();
     }
 
+    /// Play the selected piece to `to` if legal, asking for a piece first when it
+    /// promotes. Returns false if no selected move goes there.
+    fn try_move_to(&mut self, to: Square, game: &GameState) -> bool {
+        let candidates: Vec<Move> = self
+            .selected_moves
+            .iter()
+            .copied()
+            .filter(|&mv| mv.to == to || move_target(&game.board, mv) == to)
+            .collect();
+        match candidates.len() {
+            0 => return false,
+            1 => {
+                let _ = self.move_sender.send(candidates[0]);
+            }
+            _ => self.pending_promotion = Some(candidates),
+        }
+        self.clear_selection();
+        true
+    }
+
+    /// Click a piece then a square, or drag the piece and drop it.
+    fn human_input(&mut self, ctx: &egui::Context, geometry: &BoardGeometry, game: &GameState) {
+        let side = game.side_to_move();
+        let (pressed, released, pos) = ctx.input(|i| {
+            (i.pointer.primary_pressed(), i.pointer.primary_released(), i.pointer.interact_pos())
+        });
+        let Some(square) = pos.and_then(|pos| geometry.square_at(pos)) else {
+            if released {
+                self.dragging = false;
+            }
+            return;
+        };
+
+        if pressed {
+            let moved = self.selected_square.is_some() && self.try_move_to(square, game);
+            if !moved {
+                if game.board.colors(side).has(square) {
+                    self.select(game, square);
+                    self.dragging = true;
+                } else {
+                    self.clear_selection();
+                }
+            }
+        }
+        if released && self.dragging {
+            self.dragging = false;
+            // Dropping back on the start square keeps the piece selected for a click move.
+            if self.selected_square != Some(square) {
+                self.try_move_to(square, game);
+            }
+        }
+    }
+
+    fn promotion_picker(&mut self, ctx: &egui::Context, side: Color) {
+        let Some(candidates) = self.pending_promotion.clone() else {
+            return;
+        };
+        egui::Window::new("Promote to")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                        let Some(&mv) = candidates.iter().find(|mv| mv.promotion == Some(piece))
+                        else {
+                            continue;
+                        };
+                        let label = egui::RichText::new(piece_unicode(piece, side)).size(36.0);
+                        if ui.button(label).clicked() {
+                            let _ = self.move_sender.send(mv);
+                            self.pending_promotion = None;
+                        }
+                    }
+                });
+                if ui.button("Cancel").clicked() {
+                    self.pending_promotion = None;
+                }
+            });
+    }
+
+    /// Ply to take back to: the one on view, else the human's previous turn, else
+    /// the last bot move.
+    fn take_back_target(&self, game: &GameState) -> Option<usize> {
+        if let Some(ply) = self.view_ply {
+            return Some(ply);
+        }
+        let any_human = self.players.contains(&PlayerKind::Human);
+        (0..game.history.len()).rev().find(|&ply| {
+            let side = game.board_at_ply(ply).map(|b| b.side_to_move());
+            !any_human || side.is_some_and(|s| self.players[s as usize] == PlayerKind::Human)
+        })
+    }
+
+    /// Undo the live game back to `ply`; the game thread plays on from there.
+    fn take_back(&mut self, ply: usize) {
+        {
+            let mut state = self.shared.lock().unwrap();
+            while state.game.history.len() > ply {
+                state.game.undo_move();
+            }
+            state.evals.truncate(ply + 1);
+            state.step = false;
+            if let Some(clock) = state.clock.as_mut() {
+                clock.take_back(ply);
+            }
+        }
+        self.view_ply = None;
+        self.clear_selection();
+        self.pending_promotion = None;
+    }
+
+    /// Play, pause, step and speed for the bots, takeback and the next game.
+    fn playback_controls(&mut self, ui: &mut egui::Ui, game: &GameState, game_over: bool) {
+        let mut state = self.shared.lock().unwrap();
+        if self.players.iter().any(|&p| p != PlayerKind::Human) {
+            ui.horizontal(|ui| {
+                let label = if state.paused { "▶ Play" } else { "⏸ Pause" };
+                if ui.button(label).clicked() {
+                    state.paused = !state.paused;
+                    state.step = false;
+                }
+                if ui.add_enabled(state.paused, egui::Button::new("Step")).clicked() {
+                    state.step = true;
+                }
+            });
+            if !self.players.contains(&PlayerKind::Human) {
+                ui.add(
+                    egui::Slider::new(&mut state.move_delay_ms, 0..=3000)
+                        .text("ms per move"),
+                );
+            }
+        }
+        drop(state);
+
+        let target = self.take_back_target(game);
+        ui.horizontal(|ui| {
+            let label = if self.view_ply.is_some() {
+                "Take back to here"
+            } else {
+                "Take back"
+            };
+            if ui.add_enabled(target.is_some(), egui::Button::new(label)).clicked() {
+                if let Some(ply) = target {
+                    self.take_back(ply);
+                }
+            }
+            if game_over && ui.button("Next game").clicked() {
+                self.setup.advance();
+                self.start_game();
+            }
+        });
+    }
+
+    fn player_combo(ui: &mut egui::Ui, id: &str, kind: &mut PlayerKind, has_model: bool) {
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(kind.label())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(kind, PlayerKind::Human, PlayerKind::Human.label());
+                for level in ALL_LEVELS {
+                    let option = PlayerKind::Baseline(level);
+                    ui.selectable_value(kind, option, option.label());
+                }
+                ui.add_enabled_ui(has_model, |ui| {
+                    ui.selectable_value(kind, PlayerKind::Nn, PlayerKind::Nn.label());
+                });
+            });
+    }
+
+    fn setup_panel(&mut self, ui: &mut egui::Ui) {
+        let has_model = self.setup.model.is_some();
+        egui::Grid::new("players").num_columns(2).show(ui, |ui| {
+            ui.label("White");
+            Self::player_combo(ui, "white_player", &mut self.setup.white, has_model);
+            ui.end_row();
+            ui.label("Black");
+            Self::player_combo(ui, "black_player", &mut self.setup.black, has_model);
+            ui.end_row();
+        });
+        if ui.button("Swap colours").clicked() {
+            std::mem::swap(&mut self.setup.white, &mut self.setup.black);
+        }
+
+        ui.add_space(4.0);
+        ui.label("Model (.onnx)");
+        ui.horizontal(|ui| {
+            if ui.button("Browse…").clicked() {
+                if let Some(path) = self.setup.pick_model() {
+                    self.setup.model_path = path.display().to_string();
+                    self.setup.load_model();
+                }
+            }
+            // Typing the path stays possible where no file dialog is available.
+            ui.add(egui::TextEdit::singleline(&mut self.setup.model_path).desired_width(140.0));
+            if ui.button("Load").clicked() {
+                self.setup.load_model();
+            }
+        });
+        if !self.setup.model_status.is_empty() {
+            ui.small(&self.setup.model_status);
+        }
+
+        ui.add_space(4.0);
+        ui.label("Start position");
+        ui.radio_value(&mut self.setup.start, StartPosition::Standard, "Standard");
+        ui.add_enabled_ui(!self.setup.openings.is_empty(), |ui| {
+            ui.radio_value(&mut self.setup.start, StartPosition::Opening, "Opening");
+        });
+        if self.setup.start == StartPosition::Opening {
+            let selected = self.setup.opening_label(self.setup.opening_index);
+            egui::ComboBox::from_id_salt("opening")
+                .selected_text(selected)
+                .width(180.0)
+                .show_ui(ui, |ui| {
+                    for i in 0..self.setup.openings.len() {
+                        let label = self.setup.opening_label(i);
+                        ui.selectable_value(&mut self.setup.opening_index, i, label);
+                    }
+                });
+        }
+        ui.radio_value(&mut self.setup.start, StartPosition::Fen, "FEN");
+        if self.setup.start == StartPosition::Fen {
+            ui.add(egui::TextEdit::singleline(&mut self.setup.fen).hint_text("paste a FEN"));
+        }
+
+        ui.add_space(4.0);
+        ui.checkbox(&mut self.setup.timed, "Time control");
+        if self.setup.timed {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.setup.base_minutes)
+                        .range(0.5..=180.0)
+                        .speed(0.5)
+                        .suffix(" min"),
+                );
+                ui.label("+");
+                ui.add(
+                    egui::DragValue::new(&mut self.setup.increment_secs)
+                        .range(0.0..=60.0)
+                        .speed(0.5)
+                        .suffix(" s"),
+                );
+            });
+        }
+
+        ui.add_space(4.0);
+        if ui.button("New game").clicked() {
+            self.setup.reversed = false;
+            self.start_game();
+        }
+        if let Some(error) = &self.setup.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
 }
 
 impl eframe::App for ChessApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        self.replay.handle_keys(ctx);
 
-        let (game_snapshot, bot_thinking, status_message) = {
+        let (game_snapshot, bot_thinking, status_message, names, evals, clock) = {
             let state = self.shared.lock().unwrap();
-            (state.game.clone(), state.bot_thinking, state.status_message.clone())
+            (
+                state.game.clone(),
+                state.bot_thinking,
+                state.status_message.clone(),
+                state.names.clone(),
+                state.evals.clone(),
+                state.clock.clone(),
+            )
         };
+        let live_ply = game_snapshot.history.len();
+        let game_over = game_snapshot.is_game_over()
+            || clock.as_ref().is_some_and(|clock| clock.flagged.is_some());
+        if self.view_ply.is_some_and(|ply| ply >= live_ply) {
+            self.view_ply = None;
+        }
 
-        egui::SidePanel::right("info_panel").min_width(200.0).show(ctx, |ui| {
-            if self.bot_vs_bot {
-                ui.heading("NnEvalBot vs BaselineBot");
-                ui.label("NnEvalBot (White) · BaselineBot (Black)");
-            } else {
-                ui.heading("Chess Challenge");
+        // A replayed game or a compete level being watched replaces the live
+        // one on the board, and an earlier ply of the live game replaces the
+        // current position.
+        let level_position = self.level_run.position();
+        let watching_level = self.replay.selected.is_none() && level_position.is_some();
+        let (shown_board, last_move) = match (self.replay.position(), level_position) {
+            (Some((board, last)), _) => (board.clone(), last),
+            (None, Some(shown)) => shown,
+            (None, None) => {
+                let ply = self.view_ply.unwrap_or(live_ply);
+                let board = game_snapshot.board_at_ply(ply).unwrap_or(&game_snapshot.board);
+                let last = ply.checked_sub(1).map(|i| game_snapshot.history[i]);
+                (board.clone(), last)
             }
+        };
+
+        egui::SidePanel::right("info_panel").min_width(200.0).show(ctx, |ui| {
+            ui.heading("Chess Challenge");
+            ui.label(format!("{} (White) · {} (Black)", names[0], names[1]));
             ui.separator();
             ui.label(&status_message);
             if bot_thinking {
                 ui.label("Thinking...");
             }
+            if let Some(clock) = &clock {
+                clock_ui(ui, clock, &names, game_snapshot.side_to_move());
+            }
+            self.playback_controls(ui, &game_snapshot, game_over);
             ui.separator();
-            ui.heading("Move History");
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                for (i, &mv) in game_snapshot.history.iter().enumerate() {
-                    ui.label(format!(
-                        "{}. {}{}",
-                        i / 2 + 1,
-                        if i % 2 == 0 { "W: " } else { "B: " },
-                        format_move(mv),
-                    ));
+            egui::CollapsingHeader::new("New game").default_open(true).show(ui, |ui| {
+                self.setup_panel(ui);
+            });
+            egui::CollapsingHeader::new("Replay").show(ui, |ui| {
+                self.replay.ui(ui, self.setup.model.clone());
+            });
+            egui::CollapsingHeader::new("Compete level").show(ui, |ui| {
+                let (model, openings) = (self.setup.model_file.as_deref(), &self.setup.openings);
+                // Finished games open in the replay panel.
+                if let Some(i) = self.level_run.ui(ui, model, openings) {
+                    self.replay.games = self.level_run.replay_games();
+                    self.replay.status = format!("{} level games", self.replay.games.len());
+                    self.replay.losses_only = false;
+                    self.replay.select(i, self.setup.model.clone());
                 }
             });
+            egui::CollapsingHeader::new("Analysis").show(ui, |ui| {
+                self.analysis.ui(ui);
+            });
+            egui::CollapsingHeader::new("NN inputs").show(ui, |ui| {
+                let has_model = self.setup.model.is_some();
+                self.nn_view.ui(ui, &shown_board, has_model);
+            });
+            egui::CollapsingHeader::new("Board editor").show(ui, |ui| {
+                if !self.editor.active {
+                    if ui.button("Edit this position").clicked() {
+                        self.editor.load_board(&shown_board);
+                        self.editor.active = true;
+                    }
+                    return;
+                }
+                if let Some(EditorAction::Play(fen)) = self.editor.ui(ui) {
+                    self.setup.start = StartPosition::Fen;
+                    self.setup.fen = fen;
+                    self.editor.active = false;
+                    self.start_game();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.heading("Move History");
+                if ui.button("Flip").clicked() {
+                    self.flipped = !self.flipped;
+                }
+                if self.view_ply.is_some() && ui.button("Live").clicked() {
+                    self.view_ply = None;
+                }
+                if ui.button("Copy FEN").clicked() {
+                    ui.ctx().copy_text(shown_board.to_string());
+                }
+            });
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let shown = self.view_ply.unwrap_or(live_ply);
+                    for (i, mv, board) in game_snapshot.plies() {
+                        if board.side_to_move() == Color::White {
+                            ui.label(format!("{}.", board.fullmove_number()));
+                        } else if i == 0 {
+                            ui.label(format!("{}...", board.fullmove_number()));
+                        }
+                        let san = format_san(board, mv);
+                        let mut label = ui.selectable_label(shown == i + 1, san);
+                        if let Some(&time) = clock.as_ref().and_then(|c| c.think_times.get(i)) {
+                            label = label.on_hover_text(format_think_time(time));
+                        }
+                        if label.clicked() {
+                            self.view_ply = Some(i + 1);
+                        }
+                    }
+                });
+            });
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            // Eval bar left of the board, eval graph below it.
+            const BAR_WIDTH: f32 = 20.0;
+            const GRAPH_HEIGHT: f32 = 110.0;
             let available = ui.available_size();
-            let board_size = available.x.min(available.y);
-            let cell_size = board_size / 8.0;
+            let board_size = (available.x - BAR_WIDTH - 6.0)
+                .min(available.y - GRAPH_HEIGHT - 6.0)
+                .max(80.0);
 
-            let board_rect = egui::Rect::from_min_size(
-                ui.cursor().min,
-                egui::vec2(board_size, board_size),
+            let origin = ui.cursor().min;
+            let bar_rect = egui::Rect::from_min_size(origin, egui::vec2(BAR_WIDTH, board_size));
+            let geometry = BoardGeometry {
+                rect: egui::Rect::from_min_size(
+                    origin + egui::vec2(BAR_WIDTH + 6.0, 0.0),
+                    egui::vec2(board_size, board_size),
+                ),
+                cell_size: board_size / 8.0,
+                flipped: self.flipped,
+            };
+            let board_rect = geometry.rect;
+            let cell_size = geometry.cell_size;
+            let graph_rect = egui::Rect::from_min_size(
+                board_rect.left_bottom() + egui::vec2(0.0, 6.0),
+                egui::vec2(board_size, GRAPH_HEIGHT),
             );
 
-            let painter = ui.painter_at(board_rect);
+            let replaying = self.replay.selected.is_some();
+            let editing = self.editor.active;
+            let viewing = replaying || watching_level || self.view_ply.is_some() || editing;
+            let pieces = if editing {
+                *self.editor.pieces()
+            } else {
+                board_pieces(&shown_board)
+            };
 
-            // Draw squares
-            for rank in 0..8u8 {
-                for file in 0..8u8 {
-                    let sq = Square::new(
-                        File::index(file as usize),
-                        Rank::index(rank as usize),
-                    );
+            let replay_evals = if replaying {
+                self.replay.evals()
+            } else {
+                Vec::new()
+            };
+            let (graph_evals, graph_ply) = if replaying {
+                (replay_evals.as_slice(), self.replay.ply)
+            } else if watching_level {
+                (&[][..], 0)
+            } else {
+                (evals.as_slice(), self.view_ply.unwrap_or(live_ply))
+            };
+            eval_bar(&ui.painter_at(bar_rect), bar_rect, graph_evals, graph_ply);
+            if let Some(ply) = eval_graph(ui, graph_rect, graph_evals, graph_ply) {
+                if replaying {
+                    self.replay.ply = ply;
+                } else {
+                    self.view_ply = (ply < live_ply).then_some(ply);
+                }
+            }
 
-                    // Board displayed with rank 8 at top
-                    let display_rank = 7 - rank;
-                    let rect = egui::Rect::from_min_size(
-                        board_rect.min
-                            + egui::vec2(file as f32 * cell_size, display_rank as f32 * cell_size),
-                        egui::vec2(cell_size, cell_size),
-                    );
+            // The live position keeps its history (repetitions); others start fresh.
+            let analysed = if editing {
+                self.editor.validate().ok().map(GameState::from_board)
+            } else if viewing {
+                Some(GameState::from_board(shown_board.clone()))
+            } else {
+                Some(game_snapshot.clone())
+            };
+            if let Some(analysed) = &analysed {
+                self.analysis.update(analysed, self.setup.model.clone());
+            }
+
+            // Human input — only when a human is to move in the live position
+            let side = game_snapshot.side_to_move();
+            let is_human_turn = self.players[side as usize] == PlayerKind::Human
+                && !viewing
+                && !game_over
+                && !bot_thinking;
+            if is_human_turn {
+                if self.pending_promotion.is_none() {
+                    self.human_input(ctx, &geometry, &game_snapshot);
+                }
+            } else {
+                self.clear_selection();
+                self.pending_promotion = None;
+            }
+
+            let check_square = (!editing && !shown_board.checkers().is_empty())
+                .then(|| shown_board.king(shown_board.side_to_move()));
+            let targets: Vec<Square> = self
+                .selected_moves
+                .iter()
+                .flat_map(|&mv| [mv.to, move_target(&game_snapshot.board, mv)])
+                .collect();
+
+            let painter = ui.painter_at(board_rect);
+
+            // Draw squares, row 0 at the top of the screen
+            for row in 0..8 {
+                for col in 0..8 {
+                    let sq = geometry.square_at_cell(col, row);
+                    let rect = geometry.square_rect(sq);
 
-                    let is_light = (file + rank) % 2 == 1;
+                    let is_light = (sq.file() as usize + sq.rank() as usize) % 2 == 1;
                     let mut sq_color = if is_light {
                         egui::Color32::from_rgb(240, 217, 181)
                     } else {
                         egui::Color32::from_rgb(181, 136, 99)
                     };
 
-                    if Some(sq) == self.selected_square {
+                    if Some(sq) == check_square {
+                        sq_color = egui::Color32::from_rgb(220, 80, 70);
+                    } else if Some(sq) == self.selected_square {
                         sq_color = egui::Color32::from_rgb(130, 170, 80);
-                    } else if self.legal_move_targets.contains(&sq) {
+                    } else if targets.contains(&sq) {
                         sq_color = if is_light {
                             egui::Color32::from_rgb(170, 200, 120)
                         } else {
                             egui::Color32::from_rgb(120, 160, 80)
                         };
+                    } else if !editing && last_move.is_some_and(|mv| mv.from == sq || mv.to == sq) {
+                        sq_color = if is_light {
+                            egui::Color32::from_rgb(205, 210, 106)
+                        } else {
+                            egui::Color32::from_rgb(170, 162, 58)
+                        };
                     }
 
                     painter.rect_filled(rect, 0.0, sq_color);
 
-                    // Draw piece
-                    if let Some(piece) = game_snapshot.board.piece_on(sq) {
-                        let piece_color = if game_snapshot.board.colors(Color::White).has(sq) {
-                            Color::White
-                        } else {
-                            Color::Black
-                        };
+                    // Draw piece, unless it is being dragged
+                    let dragged = self.dragging && Some(sq) == self.selected_square;
+                    if let Some((piece, piece_color)) = pieces[sq as usize].filter(|_| !dragged) {
                         painter.text(
                             rect.center(),
                             egui::Align2::CENTER_CENTER,
@@ -160,220 +890,278 @@ impl eframe::App for ChessApp {
                         );
                     }
 
+                    let label_color = if is_light {
+                        egui::Color32::from_rgb(181, 136, 99)
+                    } else {
+                        egui::Color32::from_rgb(240, 217, 181)
+                    };
                     // File labels on bottom row
-                    if rank == 0 {
-                        let file_char = (b'a' + file) as char;
+                    if row == 7 {
+                        let file_char = (b'a' + sq.file() as u8) as char;
                         painter.text(
                             rect.min + egui::vec2(3.0, cell_size - 14.0),
                             egui::Align2::LEFT_BOTTOM,
                             file_char.to_string(),
                             egui::FontId::proportional(12.0),
-                            if is_light {
-                                egui::Color32::from_rgb(181, 136, 99)
-                            } else {
-                                egui::Color32::from_rgb(240, 217, 181)
-                            },
+                            label_color,
                         );
                     }
                     // Rank labels on left column
-                    if file == 0 {
+                    if col == 0 {
                         painter.text(
                             rect.min + egui::vec2(3.0, 3.0),
                             egui::Align2::LEFT_TOP,
-                            (rank + 1).to_string(),
+                            (sq.rank() as usize + 1).to_string(),
                             egui::FontId::proportional(12.0),
-                            if is_light {
-                                egui::Color32::from_rgb(181, 136, 99)
-                            } else {
-                                egui::Color32::from_rgb(240, 217, 181)
-                            },
+                            label_color,
                         );
                     }
                 }
             }
 
-            // Human click handling — disabled in bot-vs-bot mode
-            if !self.bot_vs_bot {
-                let is_human_turn = game_snapshot.side_to_move() == self.human_color
-                    && !game_snapshot.is_game_over()
-                    && !bot_thinking;
-
-                if is_human_turn {
-                    if let Some(pos) = ctx.input(|i| i.pointer.press_origin()) {
-                        if board_rect.contains(pos) && ctx.input(|i| i.pointer.primary_pressed()) {
-                            let rel = pos - board_rect.min;
-                            let file = (rel.x / cell_size) as u8;
-                            let rank = 7 - (rel.y / cell_size) as u8;
-
-                            if file < 8 && rank < 8 {
-                                let clicked_sq = Square::new(
-                                    File::index(file as usize),
-                                    Rank::index(rank as usize),
-                                );
-
-                                if let Some(from) = self.selected_square {
-                                    if self.legal_move_targets.contains(&clicked_sq) {
-                                        let promotion = {
-                                            let state = self.shared.lock().unwrap();
-                                            let is_pawn =
-                                                state.game.board.piece_on(from) == Some(Piece::Pawn);
-                                            let back_rank = match self.human_color {
-                                                Color::White => Rank::Eighth,
-                                                Color::Black => Rank::First,
-                                            };
-                                            if is_pawn && clicked_sq.rank() == back_rank {
-                                                Some(Piece::Queen)
-                                            } else {
-                                                None
-                                            }
-                                        };
-
-                                        let mv = Move { from, to: clicked_sq, promotion };
-                                        let _ = self.move_sender.send(mv);
-                                        self.selected_square = None;
-                                        self.legal_move_targets.clear();
-                                    } else {
-                                        let has_own_piece = {
-                                            let state = self.shared.lock().unwrap();
-                                            state.game.board.colors(self.human_color).has(clicked_sq)
-                                        };
-                                        if has_own_piece {
-                                            self.selected_square = Some(clicked_sq);
-                                            self.update_legal_targets(clicked_sq);
-                                        } else {
-                                            self.selected_square = None;
-                                            self.legal_move_targets.clear();
-                                        }
-                                    }
-                                } else {
-                                    let has_own_piece = {
-                                        let state = self.shared.lock().unwrap();
-                                        state.game.board.colors(self.human_color).has(clicked_sq)
-                                    };
-                                    if has_own_piece {
-                                        self.selected_square = Some(clicked_sq);
-                                        self.update_legal_targets(clicked_sq);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    self.selected_square = None;
-                    self.legal_move_targets.clear();
+            if !editing {
+                self.nn_view.update(&shown_board, self.setup.model.clone());
+                self.nn_view
+                    .draw_overlay(&painter, &shown_board, |sq| geometry.square_rect(sq));
+            }
+
+            if analysed.is_some() {
+                self.analysis
+                    .draw_arrows(&painter, cell_size, |sq| geometry.square_rect(sq).center());
+            }
+
+            if editing {
+                self.editor.board_input(ctx, |pos| geometry.square_at(pos));
+                self.editor.draw_drag(ctx, cell_size * 0.7);
+            }
+
+            // The piece being dragged follows the pointer.
+            if let (true, Some(from), Some(pos)) =
+                (self.dragging, self.selected_square, ctx.pointer_interact_pos())
+            {
+                if let Some((piece, color)) = pieces[from as usize] {
+                    ctx.layer_painter(egui::LayerId::new(
+                        egui::Order::Tooltip,
+                        egui::Id::new("drag_piece"),
+                    ))
+                    .text(
+                        pos,
+                        egui::Align2::CENTER_CENTER,
+                        piece_unicode(piece, color),
+                        egui::FontId::proportional(cell_size * 0.7),
+                        egui::Color32::BLACK,
+                    );
                 }
             }
         });
+
+        if self.pending_promotion.is_some() {
+            self.promotion_picker(ctx, game_snapshot.side_to_move());
+        }
     }
 }
 
+/// Play one game until a newer game replaces it (`generation` changes). The
+/// loop outlives the end of the game so a takeback can resume it.
 fn run_game_loop(
     shared: Arc<Mutex<SharedState>>,
-    move_receiver: std::sync::mpsc::Receiver<Move>,
-    nn_bot: Option<NnEvalBot>,
-    move_delay_ms: u64,
+    generation: u64,
+    move_receiver: Receiver<Move>,
+    players: [Player; 2],
+    evaluator: Option<Arc<NnEvalBot>>,
 ) {
-    let spicy = BaselineBot::default();
-    let bot_vs_bot = nn_bot.is_some();
+    let names = shared.lock().unwrap().names.clone();
+    let any_human = players.iter().any(|p| matches!(p, Player::Human));
+    // Position the game was in, so moves chosen before a takeback are dropped.
+    let unchanged = |state: &SharedState, game: &GameState| {
+        state.game.history.len() == game.history.len() && state.game.board == game.board
+    };
+    let poll = Duration::from_millis(50);
+    // Position a bot resigned in; the game stays over unless it is taken back.
+    let mut resigned: Option<GameState> = None;
 
     loop {
-        let (side, is_over) = {
+        // Evaluate each new position for the eval bar and graph.
+        let (game, needs_eval) = {
             let state = shared.lock().unwrap();
-            (state.game.side_to_move(), state.game.is_game_over())
+            if state.generation != generation {
+                return;
+            }
+            (state.game.clone(), state.evals.len() == state.game.history.len())
         };
-
-        if is_over {
+        if needs_eval {
+            let eval = eval_position(&game, evaluator.as_deref());
             let mut state = shared.lock().unwrap();
-            state.bot_thinking = false;
-            state.status_message = match state.game.outcome() {
-                Some(Outcome::Checkmate { winner }) => {
-                    if bot_vs_bot {
-                        format!(
-                            "{} wins by checkmate!",
-                            if winner == Color::White { "NnEvalBot" } else { "BaselineBot" }
-                        )
-                    } else {
-                        format!(
-                            "{} wins by checkmate!",
-                            if winner == Color::White { "White" } else { "Black" }
-                        )
-                    }
-                }
-                Some(Outcome::Draw) => "Draw!".to_string(),
-                None => "Game over.".to_string(),
-            };
-            break;
+            if state.generation == generation
+                && unchanged(&state, &game)
+                && state.evals.len() == game.history.len()
+            {
+                state.evals.push(eval);
+            }
         }
 
-        if bot_vs_bot {
-            let bot_name = if side == Color::White { "NnEvalBot" } else { "BaselineBot" };
+        let side = game.side_to_move();
+        let name = &names[side as usize];
+        let bot = players[side as usize].bot();
 
+        let has_resigned = resigned.as_ref().is_some_and(|r| {
+            r.history.len() == game.history.len() && r.board == game.board
+        });
+        if has_resigned {
+            thread::sleep(poll);
+            continue;
+        }
+        if game.is_game_over() {
             {
                 let mut state = shared.lock().unwrap();
-                state.bot_thinking = true;
-                state.status_message = format!("{} thinking...", bot_name);
+                if let Some(clock) = state.clock.as_mut() {
+                    clock.stop(side);
+                }
+                state.bot_thinking = false;
+                state.status_message = match game.outcome() {
+                    Some(Outcome::Checkmate { winner }) => {
+                        format!("{} wins by checkmate!", names[winner as usize])
+                    }
+                    Some(Outcome::Draw(reason)) => format!("Draw by {}!", reason.description()),
+                    None => "Game over.".to_string(),
+                };
             }
+            thread::sleep(poll);
+            continue;
+        }
 
-            let game_snapshot = shared.lock().unwrap().game.clone();
-
-            thread::sleep(Duration::from_millis(move_delay_ms));
-
-            let mv = if side == Color::White {
-                nn_bot.as_ref().unwrap().choose_move(&game_snapshot)
-            } else {
-                spicy.choose_move(&game_snapshot)
-            };
+        // Running out of time ends the game too, until a takeback.
+        let flagged = {
+            let mut state = shared.lock().unwrap();
+            state.clock.as_mut().and_then(|clock| {
+                clock.check_flag(side);
+                clock.flagged
+            })
+        };
+        if let Some(flagged) = flagged {
+            {
+                let mut state = shared.lock().unwrap();
+                state.bot_thinking = false;
+                state.status_message = flag_result(&game.board, flagged, &names);
+            }
+            thread::sleep(poll);
+            continue;
+        }
 
-            match mv {
-                Some(mv) => {
-                    let mut state = shared.lock().unwrap();
-                    state.game.make_move(mv);
-                    state.bot_thinking = false;
-                    state.status_message =
-                        format!("{} played {}", bot_name, format_move(mv));
-                }
-                None => {
-                    let winner = !side;
-                    let mut state = shared.lock().unwrap();
-                    state.bot_thinking = false;
-                    state.status_message = format!(
-                        "{} wins (opponent resigned)",
-                        if winner == Color::White { "NnEvalBot" } else { "BaselineBot" }
-                    );
-                    break;
+        let Some(bot) = bot else {
+            {
+                let mut state = shared.lock().unwrap();
+                state.status_message = format!("{} to move", name);
+                state.bot_thinking = false;
+                let paused = state.paused;
+                if let Some(clock) = state.clock.as_mut() {
+                    if paused {
+                        clock.stop(side);
+                    } else {
+                        clock.start();
+                    }
                 }
             }
-        } else {
-            // Human (White) vs BaselineBot (Black)
-            if side == Color::White {
-                {
+
+            // Time out now and then to notice takebacks.
+            match move_receiver.recv_timeout(poll) {
+                Ok(mv) => {
                     let mut state = shared.lock().unwrap();
-                    state.status_message = "White (you) to move".to_string();
-                    state.bot_thinking = false;
+                    if state.generation != generation {
+                        return;
+                    }
+                    if unchanged(&state, &game) && state.game.make_move(mv) {
+                        if let Some(clock) = state.clock.as_mut() {
+                            clock.record_move(side);
+                        }
+                    }
                 }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            continue;
+        };
+
+        // Paused bots wait for play or a single step. Their clock only runs
+        // while they think.
+        {
+            let mut state = shared.lock().unwrap();
+            if let Some(clock) = state.clock.as_mut() {
+                clock.stop(side);
+            }
+            if state.paused && !state.step {
+                state.bot_thinking = false;
+                state.status_message = format!("Paused, {} to move", name);
+                drop(state);
+                thread::sleep(poll);
+                continue;
+            }
+            let stepping = state.step;
+            state.step = false;
 
-                match move_receiver.recv() {
-                    Ok(mv) => {
-                        let mut state = shared.lock().unwrap();
-                        state.game.make_move(mv);
+            // Slow bot-vs-bot games down enough to follow.
+            if !any_human && !stepping {
+                let delay = Duration::from_millis(state.move_delay_ms);
+                drop(state);
+                let waited = std::time::Instant::now();
+                let interrupted = loop {
+                    if waited.elapsed() >= delay {
+                        break false;
                     }
-                    Err(_) => break,
-                }
-            } else {
-                {
-                    let mut state = shared.lock().unwrap();
-                    state.bot_thinking = true;
-                    state.status_message = "Bot thinking...".to_string();
+                    thread::sleep(poll.min(delay.saturating_sub(waited.elapsed())));
+                    let state = shared.lock().unwrap();
+                    if state.paused || !unchanged(&state, &game) {
+                        break true;
+                    }
+                };
+                if interrupted {
+                    continue;
                 }
+            }
+        }
 
-                let game_snapshot = shared.lock().unwrap().game.clone();
+        let time_left = {
+            let mut state = shared.lock().unwrap();
+            state.bot_thinking = true;
+            state.status_message = format!("{} thinking...", name);
+            state.clock.as_mut().map(|clock| {
+                clock.start();
+                clock.time_left(side)
+            })
+        };
 
-                if let Some(mv) = spicy.choose_move(&game_snapshot) {
-                    let mut state = shared.lock().unwrap();
-                    state.game.make_move(mv);
-                    state.bot_thinking = false;
-                    state.status_message = format!("Bot played: {}", format_move(mv));
+        let mv = match time_left {
+            Some(time) => bot.choose_move_timed(&game, &time),
+            None => bot.choose_move(&game),
+        };
+
+        let mut state = shared.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        state.bot_thinking = false;
+        if !unchanged(&state, &game) {
+            continue;
+        }
+        // A move made after the flag fell doesn't count.
+        if state.clock.as_mut().is_some_and(|clock| clock.check_flag(side)) {
+            continue;
+        }
+        match mv {
+            Some(mv) => {
+                state.game.make_move(mv);
+                if let Some(clock) = state.clock.as_mut() {
+                    clock.record_move(side);
+                }
+                state.status_message = format!("{} played {}", name, format_move(mv));
+            }
+            None => {
+                if let Some(clock) = state.clock.as_mut() {
+                    clock.stop(side);
                 }
+                state.status_message =
+                    format!("{} wins ({} resigned)", names[!side as usize], name);
+                resigned = Some(game);
             }
         }
     }
@@ -382,9 +1170,11 @@ fn run_game_loop(
 fn main() -> eframe::Result<()> {
     let args: Vec<String> = std::env::args().collect();
 
-    // Usage: gui [model.onnx] [--delay MS]
+    // Usage: gui [model.onnx] [--delay MS] [--openings <path>] [--clock MIN+INC]
     let mut nn_path: Option<PathBuf> = None;
     let mut move_delay_ms: u64 = 600;
+    let mut openings_path = String::from("data/openings.txt");
+    let mut time_control: Option<(f64, f64)> = None;
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
@@ -394,6 +1184,22 @@ fn main() -> eframe::Result<()> {
                     i += 1;
                 }
             }
+            "--openings" => {
+                if let Some(val) = args.get(i + 1) {
+                    openings_path = val.clone();
+                    i += 1;
+                }
+            }
+            "--clock" => {
+                let parsed = args.get(i + 1).and_then(|val| {
+                    let (base, inc) = val.split_once('+').unwrap_or((val, "0"));
+                    Some((base.parse::<f64>().ok()?, inc.parse::<f64>().ok()?))
+                });
+                if let Some(val) = parsed {
+                    time_control = Some(val);
+                    i += 1;
+                }
+            }
             arg if !arg.starts_with('-') && nn_path.is_none() => {
                 nn_path = Some(PathBuf::from(arg));
             }
@@ -402,42 +1208,46 @@ fn main() -> eframe::Result<()> {
         i += 1;
     }
 
-    let bot_vs_bot = nn_path.is_some();
-
-    let shared = Arc::new(Mutex::new(SharedState::new()));
-    let (tx, rx) = std::sync::mpsc::channel::<Move>();
-
-    let shared_clone = shared.clone();
-    thread::spawn(move || {
-        let nn_bot = if let Some(path) = nn_path {
-            match NnEvalBot::load(&path) {
-                Ok(b) => {
-                    println!("Loaded NnEvalBot from {}", path.display());
-                    Some(b)
-                }
-                Err(e) => {
-                    eprintln!("Failed to load NnEvalBot: {e}");
-                    shared_clone.lock().unwrap().status_message =
-                        format!("Failed to load model: {e}");
-                    return;
-                }
-            }
-        } else {
-            None
-        };
-
-        run_game_loop(shared_clone, rx, nn_bot, move_delay_ms);
+    let openings = load_opening_entries(Path::new(&openings_path)).unwrap_or_else(|e| {
+        eprintln!("Note: {e}");
+        Vec::new()
     });
 
-    let title = if bot_vs_bot {
-        "Chess Challenge — NnEvalBot vs BaselineBot"
-    } else {
-        "Chess Challenge"
+    // With a model, start watching it play White against the strongest baseline,
+    // as before; otherwise the human plays White.
+    let default_baseline = PlayerKind::Baseline(Level::new(4).unwrap());
+    let mut setup = Setup {
+        white: PlayerKind::Human,
+        black: default_baseline,
+        model_path: String::new(),
+        model: None,
+        model_file: None,
+        model_status: String::new(),
+        openings,
+        start: StartPosition::Standard,
+        opening_index: 0,
+        reversed: false,
+        fen: String::new(),
+        timed: time_control.is_some(),
+        base_minutes: time_control.map_or(5.0, |(base, _)| base),
+        increment_secs: time_control.map_or(3.0, |(_, inc)| inc),
+        error: None,
     };
+    if let Some(path) = nn_path {
+        setup.model_path = path.display().to_string();
+        setup.load_model();
+        if setup.model.is_some() {
+            println!("Loaded NnEvalBot from {}", path.display());
+            setup.white = PlayerKind::Nn;
+        } else {
+            eprintln!("{}", setup.model_status);
+        }
+    }
 
+    let title = "Chess Challenge";
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
+            .with_inner_size([900.0, 640.0])
             .with_title(title),
         ..Default::default()
     };
@@ -445,6 +1255,6 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         title,
         options,
-        Box::new(|_cc| Ok(Box::new(ChessApp::new(shared, tx, bot_vs_bot)))),
+        Box::new(move |_cc| Ok(Box::new(ChessApp::new(setup, move_delay_ms)))),
     )
 }
@@ -0,0 +1,287 @@
+//! What the network sees: the input planes of `board_to_tensor` and an occlusion
+//! saliency map, drawn over the board.
+
+use eframe::egui;
+use engine::game::GameState;
+use engine::nn::{board_to_tensor, tensor_index, HALF_SIZE, PIECE_TYPES};
+use engine::{piece_unicode, Board, Color, NnEvalBot, Square};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const PLANE_COLOR: egui::Color32 = egui::Color32::from_rgb(140, 80, 200);
+const GAIN_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 170, 80);
+const LOSS_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 60, 60);
+/// Saliency maps kept, so stepping through a game doesn't recompute them.
+const CACHE_SIZE: usize = 16;
+
+/// What is drawn over the board.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Overlay {
+    Off,
+    /// One of the 12 piece planes of the chosen perspective half.
+    Plane(usize),
+    Saliency,
+}
+
+type Saliency = Result<Vec<(Square, f32)>, String>;
+
+pub struct NnView {
+    /// 0 = the side to move's half of the tensor, 1 = the other side's.
+    pub perspective: usize,
+    pub overlay: Overlay,
+    /// FEN of the saliency map shown.
+    requested: Option<String>,
+    /// Finished saliency maps by FEN, oldest first.
+    saliency: Arc<Mutex<Vec<(String, Saliency)>>>,
+}
+
+impl Default for NnView {
+    fn default() -> Self {
+        NnView {
+            perspective: 0,
+            overlay: Overlay::Off,
+            requested: None,
+            saliency: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+/// Glyph of `channel` in the half of `owner`: its first six planes hold the
+/// owner's pieces, the other six the opponent's.
+fn plane_label(owner: Color, channel: usize) -> String {
+    let piece = PIECE_TYPES[channel % 6];
+    let color = if channel < 6 { owner } else { !owner };
+    piece_unicode(piece, color).to_string()
+}
+
+fn tensor_of(board: &Board) -> Vec<f32> {
+    board_to_tensor(&GameState::from_board(board.clone()))
+}
+
+impl NnView {
+    /// Start the saliency map of `board` in the background when it is shown.
+    pub fn update(&mut self, board: &Board, nn: Option<Arc<NnEvalBot>>) {
+        let Some(nn) = nn.filter(|_| self.overlay == Overlay::Saliency) else {
+            return;
+        };
+        let fen = board.to_string();
+        if self.requested.as_ref() == Some(&fen) {
+            return;
+        }
+        self.requested = Some(fen.clone());
+        if self.saliency.lock().unwrap().iter().any(|(f, _)| *f == fen) {
+            return;
+        }
+        let game = GameState::from_board(board.clone());
+        let cache = self.saliency.clone();
+        thread::spawn(move || {
+            let map = nn.occlusion_saliency(&game).map_err(|e| e.to_string());
+            let mut cache = cache.lock().unwrap();
+            cache.push((fen, map));
+            if cache.len() > CACHE_SIZE {
+                let _ = cache.remove(0);
+            }
+        });
+    }
+
+    /// Run `f` on the saliency map of the position shown, once it is computed.
+    fn with_saliency<R>(&self, f: impl FnOnce(&Saliency) -> R) -> Option<R> {
+        let fen = self.requested.as_ref()?;
+        let cache = self.saliency.lock().unwrap();
+        let (_, map) = cache.iter().find(|(f, _)| f == fen)?;
+        Some(f(map))
+    }
+
+    /// The chosen plane or the saliency map over the board; `square_rect` maps a
+    /// square to screen space.
+    pub fn draw_overlay(
+        &self,
+        painter: &egui::Painter,
+        board: &Board,
+        square_rect: impl Fn(Square) -> egui::Rect,
+    ) {
+        match self.overlay {
+            Overlay::Off => {}
+            Overlay::Plane(channel) => {
+                let tensor = tensor_of(board);
+                let stm = board.side_to_move();
+                let fill = egui::Color32::from_rgba_unmultiplied(
+                    PLANE_COLOR.r(),
+                    PLANE_COLOR.g(),
+                    PLANE_COLOR.b(),
+                    110,
+                );
+                for sq in Square::ALL {
+                    if tensor[tensor_index(stm, self.perspective, channel, sq)] > 0.0 {
+                        painter.rect_filled(square_rect(sq).shrink(2.0), 4.0, fill);
+                    }
+                }
+            }
+            Overlay::Saliency => {
+                if self.requested.as_deref() != Some(&board.to_string()) {
+                    return;
+                }
+                self.with_saliency(|map| {
+                    let Ok(map) = map else {
+                        return;
+                    };
+                    let max = map.iter().fold(0.0f32, |m, &(_, v)| m.max(v.abs()));
+                    if max <= 1e-6 {
+                        return;
+                    }
+                    for &(sq, value) in map {
+                        let color = if value >= 0.0 { GAIN_COLOR } else { LOSS_COLOR };
+                        let alpha = (40.0 + 150.0 * value.abs() / max) as u8;
+                        let rect = square_rect(sq);
+                        painter.rect_filled(
+                            rect,
+                            0.0,
+                            egui::Color32::from_rgba_unmultiplied(
+                                color.r(),
+                                color.g(),
+                                color.b(),
+                                alpha,
+                            ),
+                        );
+                        painter.text(
+                            rect.right_bottom() - egui::vec2(3.0, 2.0),
+                            egui::Align2::RIGHT_BOTTOM,
+                            format!("{value:+.2}"),
+                            egui::FontId::proportional(10.0),
+                            egui::Color32::BLACK,
+                        );
+                    }
+                });
+            }
+        }
+    }
+
+    /// One plane as a small 8x8 grid in tensor order: the perspective's own back
+    /// rank at the bottom.
+    fn plane_thumbnail(
+        ui: &mut egui::Ui,
+        tensor: &[f32],
+        offset: usize,
+        selected: bool,
+    ) -> egui::Response {
+        const CELL: f32 = 6.0;
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(CELL * 8.0, CELL * 8.0), egui::Sense::click());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_gray(225));
+        for i in 0..64 {
+            if tensor[offset + i] > 0.0 {
+                let (file, rank) = (i % 8, i / 8);
+                let min = rect.min + egui::vec2(file as f32, (7 - rank) as f32) * CELL;
+                let cell = egui::Rect::from_min_size(min, egui::vec2(CELL, CELL));
+                painter.rect_filled(cell, 0.0, PLANE_COLOR);
+            }
+        }
+        let stroke = if selected {
+            egui::Stroke::new(2.0, egui::Color32::YELLOW)
+        } else {
+            egui::Stroke::new(1.0, egui::Color32::from_gray(120))
+        };
+        painter.rect_stroke(rect, 0.0, stroke);
+        response
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, board: &Board, has_model: bool) {
+        let stm = board.side_to_move();
+        let name = |color: Color| {
+            if color == Color::White {
+                "White"
+            } else {
+                "Black"
+            }
+        };
+        ui.horizontal(|ui| {
+            ui.label("Half");
+            ui.radio_value(&mut self.perspective, 0, format!("{} (to move)", name(stm)));
+            ui.radio_value(&mut self.perspective, 1, name(!stm));
+        });
+
+        let tensor = tensor_of(board);
+        let half = self.perspective * HALF_SIZE;
+        let half_side = if self.perspective == 0 { stm } else { !stm };
+        ui.small("Planes as the network sees them, own back rank at the bottom.");
+        ui.small("Click one to show it on the board.");
+        egui::Grid::new("nn_planes")
+            .spacing([4.0, 4.0])
+            .show(ui, |ui| {
+                for (row, owner) in ["own", "their"].into_iter().enumerate() {
+                    ui.label(owner);
+                    for kind in 0..6 {
+                        let channel = row * 6 + kind;
+                        let selected = self.overlay == Overlay::Plane(channel);
+                        ui.vertical(|ui| {
+                            ui.label(plane_label(half_side, channel));
+                            let response =
+                                Self::plane_thumbnail(ui, &tensor, half + channel * 64, selected);
+                            if response.clicked() {
+                                self.overlay = if selected {
+                                    Overlay::Off
+                                } else {
+                                    Overlay::Plane(channel)
+                                };
+                            }
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+        ui.label(format!(
+            "Castling inputs: kingside {}, queenside {}",
+            tensor[half + 768],
+            tensor[half + 769]
+        ));
+
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            let saliency = self.overlay == Overlay::Saliency;
+            let button = egui::SelectableLabel::new(saliency, "Occlusion saliency");
+            if ui.add_enabled(has_model, button).clicked() {
+                self.overlay = if saliency {
+                    Overlay::Off
+                } else {
+                    Overlay::Saliency
+                };
+            }
+            if self.overlay != Overlay::Off && ui.button("Hide overlay").clicked() {
+                self.overlay = Overlay::Off;
+            }
+        });
+        if !has_model {
+            ui.small("Load a model for the saliency map.");
+        }
+        if self.overlay != Overlay::Saliency {
+            return;
+        }
+        ui.small("Eval each piece's owner loses when it is removed.");
+        ui.small("Red pieces are ones the network would rather not have.");
+        let shown = self.with_saliency(|map| match map {
+            Err(e) => {
+                ui.colored_label(LOSS_COLOR, format!("NN failed: {e}"));
+            }
+            Ok(map) => {
+                let mut ranked = map.clone();
+                ranked.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+                egui::Grid::new("saliency_top")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (sq, value) in ranked.into_iter().take(8) {
+                            let piece = board.piece_on(sq).zip(board.color_on(sq));
+                            let glyph = piece.map(|(p, c)| piece_unicode(p, c)).unwrap_or("");
+                            ui.label(format!("{glyph} {sq}"));
+                            let color = if value >= 0.0 { GAIN_COLOR } else { LOSS_COLOR };
+                            ui.colored_label(color, format!("{value:+.3}"));
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+        if shown.is_none() {
+            ui.label("Computing...");
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! The position being edited, kept apart from the editor's widgets.
+
+use engine::{Board, Color, File, Piece, Rank, Square};
+
+/// Piece on each square, indexed by `Square as usize` (a1 = 0, h8 = 63).
+pub type Pieces = [Option<(Piece, Color)>; 64];
+
+pub fn board_pieces(board: &Board) -> Pieces {
+    let mut pieces = [None; 64];
+    for (i, slot) in pieces.iter_mut().enumerate() {
+        let sq = Square::new(File::index(i % 8), Rank::index(i / 8));
+        *slot = board.piece_on(sq).zip(board.color_on(sq));
+    }
+    pieces
+}
+
+fn fen_char(piece: Piece, color: Color) -> char {
+    let c = match piece {
+        Piece::Pawn => 'p',
+        Piece::Knight => 'n',
+        Piece::Bishop => 'b',
+        Piece::Rook => 'r',
+        Piece::Queen => 'q',
+        Piece::King => 'k',
+    };
+    if color == Color::White {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+/// A position as the editor lays it out: pieces, side to move, castling flags and
+/// en passant file, any of which may be inconsistent until `validate` passes.
+pub struct EditedPosition {
+    pub pieces: Pieces,
+    pub side_to_move: Color,
+    /// White short, White long, Black short, Black long.
+    pub castling: [bool; 4],
+    pub en_passant: Option<File>,
+}
+
+impl EditedPosition {
+    pub fn from_board(board: &Board) -> EditedPosition {
+        let white = board.castle_rights(Color::White);
+        let black = board.castle_rights(Color::Black);
+        EditedPosition {
+            pieces: board_pieces(board),
+            side_to_move: board.side_to_move(),
+            castling: [
+                white.short.is_some(),
+                white.long.is_some(),
+                black.short.is_some(),
+                black.long.is_some(),
+            ],
+            en_passant: board.en_passant(),
+        }
+    }
+
+    /// An empty board with no castling or en passant.
+    pub fn clear(&mut self) {
+        self.pieces = [None; 64];
+        self.castling = [false; 4];
+        self.en_passant = None;
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.pieces[rank * 8 + file] {
+                    Some((piece, color)) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(fen_char(piece, color));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+        let side = if self.side_to_move == Color::White {
+            "w"
+        } else {
+            "b"
+        };
+        let castling: String = self
+            .castling
+            .iter()
+            .zip("KQkq".chars())
+            .filter(|(&on, _)| on)
+            .map(|(_, c)| c)
+            .collect();
+        let castling = if castling.is_empty() {
+            "-".to_string()
+        } else {
+            castling
+        };
+        let en_passant = match self.en_passant {
+            Some(file) => {
+                let rank = if self.side_to_move == Color::White {
+                    '6'
+                } else {
+                    '3'
+                };
+                format!("{}{}", (b'a' + file as u8) as char, rank)
+            }
+            None => "-".to_string(),
+        };
+        format!("{placement} {side} {castling} {en_passant} 0 1")
+    }
+
+    /// The edited position, if cozy-chess accepts it.
+    pub fn validate(&self) -> Result<Board, String> {
+        self.to_fen()
+            .parse::<Board>()
+            .map_err(|e| format!("Invalid position: {e:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::game::STARTPOS;
+
+    fn position(fen: &str) -> EditedPosition {
+        EditedPosition::from_board(&fen.parse().unwrap())
+    }
+
+    #[test]
+    fn round_trips_castling_and_en_passant() {
+        assert_eq!(
+            EditedPosition::from_board(&Board::default()).to_fen(),
+            STARTPOS
+        );
+
+        // Move counters are not edited and restart at 0 1.
+        let fen = "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3";
+        let edited = position(fen);
+        assert_eq!(edited.castling, [true, false, false, true]);
+        assert_eq!(edited.en_passant, Some(File::E));
+        assert_eq!(
+            edited.to_fen(),
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 1"
+        );
+        assert!(edited.validate().is_ok());
+
+        let mut white = position("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 2");
+        assert_eq!(white.to_fen(), "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1");
+        white.en_passant = None;
+        white.castling = [false; 4];
+        assert_eq!(white.to_fen(), "4k3/8/8/3Pp3/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn validation_rejects_impossible_setups() {
+        let mut empty = EditedPosition::from_board(&Board::default());
+        empty.clear();
+        assert_eq!(empty.to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+        assert!(empty.validate().is_err());
+
+        // Castling flags need the king and rook on their squares.
+        let mut castling = position("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(castling.validate().is_ok());
+        castling.castling[1] = true;
+        assert!(castling.validate().is_err());
+
+        // En passant needs a pawn that could just have made a double step.
+        let mut en_passant = position("4k3/8/8/3Pp3/8/8/8/4K3 w - - 0 1");
+        en_passant.en_passant = Some(File::A);
+        assert!(en_passant.validate().is_err());
+        en_passant.en_passant = Some(File::E);
+        assert!(en_passant.validate().is_ok());
+        en_passant.side_to_move = Color::Black;
+        assert!(en_passant.validate().is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Replay of finished games from `compete --json-output` results or PGN files.
+
+use crate::eval_view::{eval_position, PlyEval};
+use crate::replay_games::{load_replay_games, ReplayGame};
+use eframe::egui;
+use engine::game::GameState;
+use engine::{Board, Color, Move, NnEvalBot};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The "Replay" panel: loaded games, the selected one and the ply shown.
+#[derive(Default)]
+pub struct Replay {
+    pub path: String,
+    pub status: String,
+    pub games: Vec<ReplayGame>,
+    pub losses_only: bool,
+    /// Game on the board; `None` shows the live game.
+    pub selected: Option<usize>,
+    pub ply: usize,
+    /// Evaluation of every position of the selected game. The static scores are
+    /// there at once; the NN's are filled in by a background thread.
+    evals: Arc<Mutex<Vec<PlyEval>>>,
+}
+
+impl Replay {
+    /// The game being replayed, if any.
+    pub fn game(&self) -> Option<&ReplayGame> {
+        self.selected.and_then(|i| self.games.get(i))
+    }
+
+    /// Position on the board and the move that led to it.
+    pub fn position(&self) -> Option<(&Board, Option<Move>)> {
+        let game = self.game()?;
+        let last = self.ply.checked_sub(1).map(|i| game.moves[i]);
+        Some((&game.boards[self.ply], last))
+    }
+
+    fn open(&mut self) {
+        match load_replay_games(Path::new(self.path.trim())) {
+            Ok(games) => {
+                self.status = format!("{} games", games.len());
+                self.games = games;
+            }
+            Err(e) => {
+                self.status = e;
+                self.games.clear();
+            }
+        }
+        self.selected = None;
+        self.ply = 0;
+    }
+
+    /// Arrow keys step through the selected game; Home/End jump to either end.
+    pub fn handle_keys(&mut self, ctx: &egui::Context) {
+        let Some(last) = self.game().map(|g| g.moves.len()) else {
+            return;
+        };
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        ctx.input(|i| {
+            if i.key_pressed(egui::Key::ArrowLeft) {
+                self.ply = self.ply.saturating_sub(1);
+            }
+            if i.key_pressed(egui::Key::ArrowRight) {
+                self.ply = (self.ply + 1).min(last);
+            }
+            if i.key_pressed(egui::Key::Home) {
+                self.ply = 0;
+            }
+            if i.key_pressed(egui::Key::End) {
+                self.ply = last;
+            }
+        });
+    }
+
+    /// Show game `index` from its start, evaluating its positions with `nn` in the
+    /// background.
+    pub fn select(&mut self, index: usize, nn: Option<Arc<NnEvalBot>>) {
+        self.selected = Some(index);
+        self.ply = 0;
+        let boards = self.games[index].boards.clone();
+        let evals = boards
+            .iter()
+            .map(|board| eval_position(&GameState::from_board(board.clone()), None))
+            .collect();
+        // A fresh vector, so a thread still working on the previous game
+        // fills one nobody reads any more.
+        self.evals = Arc::new(Mutex::new(evals));
+        let Some(nn) = nn else {
+            return;
+        };
+        let shared = self.evals.clone();
+        thread::spawn(move || {
+            for (i, board) in boards.into_iter().enumerate() {
+                let eval = eval_position(&GameState::from_board(board), Some(&nn));
+                shared.lock().unwrap()[i] = eval;
+            }
+        });
+    }
+
+    /// Evaluations of the selected game so far.
+    pub fn evals(&self) -> Vec<PlyEval> {
+        self.evals.lock().unwrap().clone()
+    }
+
+    /// `nn`, when loaded, adds the network's view to the eval graph of the selected game.
+    pub fn ui(&mut self, ui: &mut egui::Ui, nn: Option<Arc<NnEvalBot>>) {
+        ui.label("compete JSON or PGN file");
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(140.0));
+            if ui.button("Open").clicked() {
+                self.open();
+            }
+        });
+        if !self.status.is_empty() {
+            ui.small(&self.status);
+        }
+        if self.games.is_empty() {
+            return;
+        }
+        ui.checkbox(&mut self.losses_only, "NN losses only");
+        let mut select = None;
+        egui::ScrollArea::vertical()
+            .id_salt("replay_games")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for (i, game) in self.games.iter().enumerate() {
+                    if self.losses_only && game.outcome != "loss" {
+                        continue;
+                    }
+                    if ui
+                        .selectable_label(self.selected == Some(i), game.label())
+                        .clicked()
+                    {
+                        select = Some(i);
+                    }
+                }
+            });
+
+        if let Some(i) = select {
+            self.select(i, nn);
+        }
+
+        let Some(game) = self.game() else {
+            return;
+        };
+        ui.separator();
+        if let Some(side) = game.nn_side {
+            ui.label(format!(
+                "NN played {}",
+                if side == Color::White {
+                    "White"
+                } else {
+                    "Black"
+                }
+            ));
+        }
+        let last = game.moves.len();
+        let mut ply = self.ply;
+        ui.horizontal(|ui| {
+            if ui.button("⏮").clicked() {
+                ply = 0;
+            }
+            if ui.button("◀").clicked() {
+                ply = ply.saturating_sub(1);
+            }
+            ui.label(format!("{ply}/{last}"));
+            if ui.button("▶").clicked() {
+                ply = (ply + 1).min(last);
+            }
+            if ui.button("⏭").clicked() {
+                ply = last;
+            }
+        });
+        ui.small("← / → step, Home / End jump");
+
+        // Start from the first position's move number and side to move.
+        let start = &game.boards[0];
+        let offset = (start.side_to_move() == Color::Black) as usize;
+        let first_move = start.fullmove_number() as usize;
+        egui::ScrollArea::vertical()
+            .id_salt("replay_moves")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for (i, san) in game.sans.iter().enumerate() {
+                        let n = i + offset;
+                        if n.is_multiple_of(2) {
+                            ui.label(format!("{}.", first_move + n / 2));
+                        } else if i == 0 {
+                            ui.label(format!("{first_move}..."));
+                        }
+                        if ui.selectable_label(ply == i + 1, san).clicked() {
+                            ply = i + 1;
+                        }
+                    }
+                });
+            });
+        self.ply = ply;
+
+        if ui.button("Back to live game").clicked() {
+            self.selected = None;
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Games listed by the replay panel, read from `compete --json-output` results or
+//! PGN files.
+
+use engine::json::{parse_json, Json};
+use engine::pgn::parse_pgn;
+use engine::uci::parse_uci_move;
+use engine::{format_move, parse_san, Board, Color, Move};
+use std::path::Path;
+
+/// One finished game loaded for replay.
+pub struct ReplayGame {
+    pub level: Option<u8>,
+    pub opening: String,
+    /// Side the NN played, when known.
+    pub nn_side: Option<Color>,
+    /// "win", "loss" or "draw" from the NN's point of view, else the PGN result.
+    pub outcome: String,
+    /// Position before the first move, then after every ply.
+    pub boards: Vec<Board>,
+    pub moves: Vec<Move>,
+    pub sans: Vec<String>,
+}
+
+impl ReplayGame {
+    pub fn label(&self) -> String {
+        let level = self.level.map(|l| format!("L{l} ")).unwrap_or_default();
+        let side = match self.nn_side {
+            Some(Color::White) => "NN white",
+            Some(Color::Black) => "NN black",
+            None => "-",
+        };
+        format!("{level}{}  {side}  {}", self.opening, self.outcome)
+    }
+}
+
+fn games_from_json(doc: &Json) -> Result<Vec<ReplayGame>, String> {
+    let levels = doc
+        .get("levels")
+        .and_then(Json::as_array)
+        .ok_or("missing \"levels\" array")?;
+    let mut games = Vec::new();
+    for level in levels {
+        let level_num = level.get("level").and_then(Json::as_f64).map(|n| n as u8);
+        for game in level.get("games").and_then(Json::as_array).unwrap_or(&[]) {
+            let index = game
+                .get("opening_index")
+                .and_then(Json::as_f64)
+                .unwrap_or(0.0) as usize;
+            let fen = game
+                .get("starting_fen")
+                .and_then(Json::as_str)
+                .unwrap_or_default();
+            let start: Board = fen
+                .parse()
+                .map_err(|e| format!("invalid starting_fen {fen}: {e:?}"))?;
+
+            // Positions come from the FENs stored with each move; the UCI move is kept
+            // for highlighting.
+            let mut boards = vec![start];
+            let mut moves = Vec::new();
+            let mut sans = Vec::new();
+            for mv in game.get("moves").and_then(Json::as_array).unwrap_or(&[]) {
+                let uci = mv.get("uci").and_then(Json::as_str).unwrap_or("");
+                let fen = mv.get("fen").and_then(Json::as_str).unwrap_or("");
+                let (Some(m), Ok(board)) = (parse_uci_move(uci), fen.parse::<Board>()) else {
+                    return Err(format!("bad move record {uci} / {fen}"));
+                };
+                let san = mv.get("san").and_then(Json::as_str).map(str::to_string);
+                sans.push(san.unwrap_or_else(|| format_move(m)));
+                moves.push(m);
+                boards.push(board);
+            }
+
+            let name = game.get("opening_name").and_then(Json::as_str);
+            let color = game.get("color").and_then(Json::as_str).unwrap_or("white");
+            games.push(ReplayGame {
+                level: level_num,
+                opening: match name {
+                    Some(name) => format!("#{} {name}", index + 1),
+                    None => format!("#{}", index + 1),
+                },
+                nn_side: Some(if color == "black" {
+                    Color::Black
+                } else {
+                    Color::White
+                }),
+                outcome: game
+                    .get("outcome")
+                    .and_then(Json::as_str)
+                    .unwrap_or("?")
+                    .to_string(),
+                boards,
+                moves,
+                sans,
+            });
+        }
+    }
+    Ok(games)
+}
+
+fn games_from_pgn(text: &str) -> Result<Vec<ReplayGame>, String> {
+    let mut games = Vec::new();
+    for (i, pgn) in parse_pgn(text).into_iter().enumerate() {
+        let start: Board = match pgn.tag("FEN") {
+            Some(fen) => fen
+                .parse()
+                .map_err(|e| format!("game {}: invalid FEN {fen}: {e:?}", i + 1))?,
+            None => Board::default(),
+        };
+        let mut boards = vec![start];
+        let mut moves = Vec::new();
+        for san in &pgn.moves {
+            let mut board = boards.last().unwrap().clone();
+            let Some(m) = parse_san(&board, san) else {
+                return Err(format!("game {}: illegal move {san}", i + 1));
+            };
+            board.play_unchecked(m);
+            moves.push(m);
+            boards.push(board);
+        }
+
+        let is_nn = |tag: &str| pgn.tag(tag).is_some_and(|name| name.contains("NnEvalBot"));
+        let nn_side = if is_nn("White") {
+            Some(Color::White)
+        } else if is_nn("Black") {
+            Some(Color::Black)
+        } else {
+            None
+        };
+        let result = pgn.tag("Result").or(pgn.result.as_deref()).unwrap_or("*");
+        let outcome = match (nn_side, result) {
+            (Some(_), "1/2-1/2") => "draw",
+            (Some(Color::White), "1-0") | (Some(Color::Black), "0-1") => "win",
+            (Some(Color::White), "0-1") | (Some(Color::Black), "1-0") => "loss",
+            _ => result,
+        };
+        let level = pgn
+            .tag("Event")
+            .and_then(|e| e.rsplit(' ').next())
+            .and_then(|n| n.parse().ok());
+        let opening = match (pgn.tag("ECO"), pgn.tag("Opening")) {
+            (Some(eco), Some(name)) => format!("{eco} {name}"),
+            (_, Some(name)) => name.to_string(),
+            _ => match (pgn.tag("White"), pgn.tag("Black")) {
+                (Some(w), Some(b)) => format!("{w} vs {b}"),
+                _ => format!("game {}", i + 1),
+            },
+        };
+        games.push(ReplayGame {
+            level,
+            opening,
+            nn_side,
+            outcome: outcome.to_string(),
+            boards,
+            moves,
+            sans: pgn.moves.clone(),
+        });
+    }
+    Ok(games)
+}
+
+/// Load every game from a compete JSON results file or a PGN file.
+pub fn load_replay_games(path: &Path) -> Result<Vec<ReplayGame>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let source = path.display();
+    if text.trim_start().starts_with('{') {
+        let doc = parse_json(&text).map_err(|e| format!("{source}: {e}"))?;
+        games_from_json(&doc).map_err(|e| format!("{source}: {e}"))
+    } else {
+        games_from_pgn(&text).map_err(|e| format!("{source}: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+    const AFTER_E5: &str = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+
+    fn json_games(text: &str) -> Result<Vec<ReplayGame>, String> {
+        games_from_json(&parse_json(text).unwrap())
+    }
+
+    #[test]
+    fn lists_compete_json_games() {
+        let text = format!(
+            r#"{{"levels":[{{"level":2,"games":[{{"opening_index":4,"opening_name":"King's Pawn",
+            "color":"black","outcome":"loss","starting_fen":"{}","moves":[
+            {{"uci":"e2e4","san":"e4","fen":"{AFTER_E4}"}},{{"uci":"e7e5","fen":"{AFTER_E5}"}}]}}]}}]}}"#,
+            Board::default()
+        );
+        let games = json_games(&text).unwrap();
+        assert_eq!(games.len(), 1);
+        let game = &games[0];
+        assert_eq!(game.label(), "L2 #5 King's Pawn  NN black  loss");
+        assert_eq!(game.sans, ["e4", "e7e5"]);
+        assert_eq!(game.boards.len(), 3);
+        assert_eq!(game.boards[2].to_string(), AFTER_E5);
+        assert_eq!(game.moves[1], parse_uci_move("e7e5").unwrap());
+    }
+
+    #[test]
+    fn rejects_broken_json_games() {
+        assert!(json_games("{}").is_err());
+        let bad_fen = r#"{"levels":[{"games":[{"starting_fen":"not a fen"}]}]}"#;
+        assert!(json_games(bad_fen).err().unwrap().contains("not a fen"));
+        let bad_move = format!(
+            r#"{{"levels":[{{"games":[{{"starting_fen":"{AFTER_E4}","moves":[{{"uci":"zz"}}]}}]}}]}}"#
+        );
+        let err = json_games(&bad_move).err().unwrap();
+        assert!(err.starts_with("bad move record"), "{err}");
+    }
+
+    #[test]
+    fn lists_pgn_games_from_the_nns_side() {
+        let text = r#"[Event "Chess Challenge Level 3"]
+[White "BaselineBot L3 (Club)"]
+[Black "NnEvalBot"]
+[Result "1-0"]
+[ECO "C20"]
+[Opening "King's Pawn"]
+
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0
+
+[White "A"]
+[Black "B"]
+[SetUp "1"]
+[FEN "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"]
+
+1... c5 *
+"#;
+        let games = games_from_pgn(text).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].label(), "L3 C20 King's Pawn  NN black  loss");
+        assert_eq!(games[0].boards.len(), 8);
+        assert_eq!(games[1].label(), "A vs B  -  *");
+        assert_eq!(games[1].boards[0].to_string(), AFTER_E4);
+        assert_eq!(games[1].sans, ["c5"]);
+    }
+
+    #[test]
+    fn rejects_illegal_pgn_moves() {
+        let err = games_from_pgn("1. e4 e4 *\n").err().unwrap();
+        assert_eq!(err, "game 1: illegal move e4");
+    }
+}